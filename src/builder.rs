@@ -1,7 +1,13 @@
 use crate::config::*;
-use crate::handler::{LazyClient, RocketHandler};
-use lambda_http::lambda;
+use crate::handler::RocketHandler;
+use http::header::HeaderName;
+use lambda_http::{lambda, Body, Request, Response};
+use once_cell::sync::OnceCell;
+use rocket::local::{LocalRequest, LocalResponse};
 use rocket::Rocket;
+use std::ops::RangeInclusive;
+use std::sync::Mutex;
+use std::time::Duration;
 
 /// A builder to create and configure a [RocketHandler](RocketHandler).
 pub struct RocketHandlerBuilder {
@@ -39,11 +45,25 @@ impl RocketHandlerBuilder {
     /// let handler = rocket::ignite().lambda().into_handler();
     /// lambda!(handler);
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// If [eager_client](RocketHandlerBuilder::eager_client) has been called, this panics if the base path behaviour is
+    /// `RemountAndInclude` and no [base_path](RocketHandlerBuilder::base_path) has been set.
+    ///
+    /// If the `Rocket` used to create the builder was misconfigured, this does not panic. Instead, the error is logged and
+    /// every request handled by the `RocketHandler` will fail.
     pub fn into_handler(self) -> RocketHandler {
-        RocketHandler {
-            client: LazyClient::Uninitialized(self.rocket),
+        let handler = RocketHandler {
+            rocket: Mutex::new(Some(self.rocket)),
+            client: OnceCell::new(),
+            detected_base_path: OnceCell::new(),
             config: self.config,
+        };
+        if handler.config.eager_client {
+            handler.initialize_eager_client();
         }
+        handler
     }
 
     /// Starts handling Lambda events by polling for events using Lambda's Runtime APIs.
@@ -52,7 +72,7 @@ impl RocketHandlerBuilder {
     ///
     /// # Panics
     ///
-    /// This panics if the required Lambda runtime environment variables are not set, or if the `Rocket` used to create the builder was misconfigured.
+    /// This panics if the required Lambda runtime environment variables are not set.
     ///
     /// # Example
     ///
@@ -116,16 +136,14 @@ impl RocketHandlerBuilder {
     /// assert_eq!(builder.get_response_type("application/json"), ResponseType::Auto);
     /// ```
     pub fn get_response_type(&self, content_type: &str) -> ResponseType {
-        self.config
-            .response_types
-            .get(&content_type.to_lowercase())
-            .copied()
-            .unwrap_or(self.config.default_response_type)
+        self.config.response_type_for(content_type)
     }
 
     /// Sets the `ResponseType` for responses with the given Content-Type header.
     ///
-    /// `content_type` values are treated case-insensitively.
+    /// `content_type` values are treated case-insensitively. A `content_type` ending in `/*`, e.g. `image/*`,
+    /// matches any Content-Type with that top-level type, unless a `ResponseType` has been set for that
+    /// exact Content-Type.
     ///
     /// # Example
     ///
@@ -134,9 +152,13 @@ impl RocketHandlerBuilder {
     ///
     /// let builder = rocket::ignite()
     ///     .lambda()
-    ///     .response_type("TEXT/PLAIN", ResponseType::Text);
+    ///     .response_type("TEXT/PLAIN", ResponseType::Text)
+    ///     .response_type("image/*", ResponseType::Binary)
+    ///     .response_type("image/svg+xml", ResponseType::Text);
     /// assert_eq!(builder.get_response_type("text/plain"), ResponseType::Text);
     /// assert_eq!(builder.get_response_type("application/json"), ResponseType::Auto);
+    /// assert_eq!(builder.get_response_type("image/png"), ResponseType::Binary);
+    /// assert_eq!(builder.get_response_type("image/svg+xml"), ResponseType::Text);
     /// ```
     pub fn response_type(mut self, content_type: &str, response_type: ResponseType) -> Self {
         self.config
@@ -145,6 +167,201 @@ impl RocketHandlerBuilder {
         self
     }
 
+    /// Sets the `ResponseType` to `Binary` for responses with any of the given Content-Type headers.
+    ///
+    /// `content_types` values are treated case-insensitively.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::{RocketExt, ResponseType};
+    ///
+    /// let builder = rocket::ignite()
+    ///     .lambda()
+    ///     .binary_content_types(&["image/png", "IMAGE/JPEG"]);
+    /// assert_eq!(builder.get_response_type("image/png"), ResponseType::Binary);
+    /// assert_eq!(builder.get_response_type("image/jpeg"), ResponseType::Binary);
+    /// assert_eq!(builder.get_response_type("text/plain"), ResponseType::Auto);
+    /// ```
+    pub fn binary_content_types(mut self, content_types: &[&str]) -> Self {
+        for content_type in content_types {
+            self.config
+                .response_types
+                .insert(content_type.to_lowercase(), ResponseType::Binary);
+        }
+        self
+    }
+
+    /// Sets the `ResponseType` to `Binary` for responses with common binary Content-Type headers,
+    /// such as images, fonts, PDFs and `application/octet-stream`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::{RocketExt, ResponseType};
+    ///
+    /// let builder = rocket::ignite().lambda().default_binary_content_types();
+    /// assert_eq!(builder.get_response_type("image/png"), ResponseType::Binary);
+    /// assert_eq!(builder.get_response_type("application/pdf"), ResponseType::Binary);
+    /// assert_eq!(builder.get_response_type("text/plain"), ResponseType::Auto);
+    /// ```
+    pub fn default_binary_content_types(self) -> Self {
+        self.binary_content_types(DEFAULT_BINARY_CONTENT_TYPES)
+    }
+
+    /// Sets the `ResponseType` for responses with a status code in the given range, regardless of their Content-Type.
+    /// This takes precedence over all other response type configuration, e.g. so that error responses are encoded as
+    /// text when the [default_response_type](RocketHandlerBuilder::default_response_type) is `Binary`.
+    ///
+    /// If a status code is in more than one range, the range that was set last is used.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::{RocketExt, ResponseType};
+    ///
+    /// let builder = rocket::ignite()
+    ///     .lambda()
+    ///     .default_response_type(ResponseType::Binary)
+    ///     .response_type_for_status(400..=599, ResponseType::Text);
+    /// ```
+    pub fn response_type_for_status(
+        mut self,
+        statuses: RangeInclusive<u16>,
+        response_type: ResponseType,
+    ) -> Self {
+        self.config
+            .status_response_types
+            .push((statuses, response_type));
+        self
+    }
+
+    /// Determines whether responses with a `ResponseType` of `Text` are encoded as base-64 when their content is
+    /// not valid UTF-8. The default is `false`, which causes such responses to fail.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::{RocketExt, ResponseType};
+    ///
+    /// let builder = rocket::ignite()
+    ///     .lambda()
+    ///     .default_response_type(ResponseType::Text)
+    ///     .text_fallback_to_binary(true);
+    /// ```
+    pub fn text_fallback_to_binary(mut self, setting: bool) -> Self {
+        self.config.text_fallback_to_binary = setting;
+        self
+    }
+
+    /// Sets whether responses with a `Content-Disposition: attachment` header, e.g. file downloads, are always
+    /// encoded as base-64, regardless of their Content-Type. The default is `false`.
+    ///
+    /// This takes precedence over the `ResponseType` set for the response's Content-Type, but not over a
+    /// [response_type_resolver](RocketHandlerBuilder::response_type_resolver) or the response's
+    /// [RESPONSE_TYPE_HEADER](crate::RESPONSE_TYPE_HEADER) header.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite()
+    ///     .lambda()
+    ///     .binary_attachments(true);
+    /// ```
+    pub fn binary_attachments(mut self, setting: bool) -> Self {
+        self.config.binary_attachments = setting;
+        self
+    }
+
+    /// Sets the maximum size in bytes of response bodies, after they have been encoded as text or base-64.
+    /// Responses with larger bodies will fail, rather than producing a payload that would be rejected by
+    /// the Lambda runtime or API Gateway. There is no maximum by default.
+    ///
+    /// Lambda limits the size of synchronous invocation responses to 6MB, which includes the headers and
+    /// other fields of the response as well as the body.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite()
+    ///     .lambda()
+    ///     .max_response_size(5_000_000)
+    ///     .error_response(500);
+    /// ```
+    pub fn max_response_size(mut self, max_size: usize) -> Self {
+        self.config.max_response_size = Some(max_size);
+        self
+    }
+
+    /// Sets the maximum size in bytes of response bodies, after they have been encoded as text or base-64. Responses
+    /// with larger bodies are truncated to this size, and have their [TRUNCATED_HEADER](crate::TRUNCATED_HEADER) header
+    /// set to `true` so that clients can detect that the body is incomplete. Text bodies are truncated at a character
+    /// boundary, so may be slightly shorter than the maximum size. By default, responses are not truncated.
+    ///
+    /// This is applied before [max_response_size](RocketHandlerBuilder::max_response_size), so a truncated response
+    /// will not fail if its size is no larger than the maximum size set there.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite()
+    ///     .lambda()
+    ///     .truncate_oversized_responses(5_000_000);
+    /// ```
+    pub fn truncate_oversized_responses(mut self, max_size: usize) -> Self {
+        self.config.truncate_response_size = Some(max_size);
+        self
+    }
+
+    /// Sets the maximum size in bytes of request bodies, after they have been decoded from base-64 (if necessary).
+    /// Requests with larger bodies are not passed to Rocket, and a 413 (Payload Too Large) response is returned
+    /// instead. There is no maximum by default.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite()
+    ///     .lambda()
+    ///     .max_request_size(1_000_000);
+    /// ```
+    pub fn max_request_size(mut self, max_size: usize) -> Self {
+        self.config.max_request_size = Some(max_size);
+        self
+    }
+
+    /// Decompresses request bodies with a `Content-Encoding` of `gzip` or `deflate` before they are passed to Rocket.
+    /// The `Content-Encoding` header is removed from these requests, and their `Content-Length` header is set to the
+    /// size of the decompressed body. A 400 (Bad Request) response is returned for requests whose bodies cannot be
+    /// decompressed. This is disabled by default.
+    ///
+    /// The [max_request_size](RocketHandlerBuilder::max_request_size) applies to both the compressed and decompressed
+    /// body, and a 413 (Payload Too Large) response is returned if either is too large. If no maximum request size has
+    /// been set, decompressed bodies are limited to 10 MiB, so that a small compressed body cannot exhaust the
+    /// function's memory.
+    ///
+    /// Compressed bodies are only received intact if API Gateway treats them as binary, e.g. by including their
+    /// Content-Type in the API's binary media types.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite().lambda().decompress_requests();
+    /// ```
+    pub fn decompress_requests(mut self) -> Self {
+        self.config.decompress_requests = true;
+        self
+    }
+
     /// Determines whether the API Gateway base path is included in the URL processed by Rocket.
     /// The default is `RemountAndInclude`.
     ///
@@ -158,6 +375,15 @@ impl RocketHandlerBuilder {
     /// The possible values are:
     /// - `RemountAndInclude` - Includes the base bath in the URL. The first request received will be used to determine
     /// the base path, and all mounted routes will be cloned and re-mounted at the base path.
+    /// - `IncludeFromFirstRequest` - Includes the base bath in the URL. The first request received will be used to determine
+    /// the base path, which is then prepended to the path of every request. Routes are not re-mounted, so you must ensure
+    /// that the `Rocket` handles the base path, e.g. by mounting routes at the expected base path or by using a fairing that
+    /// rewrites request URIs. Unlike `RemountAndInclude`, this does not clone every route, so it is cheaper for large apps
+    /// and cannot cause route collisions.
+    /// - `ExcludeStage` - Excludes the stage from the URL when the API is called using the default API Gateway URL, but
+    /// includes the base path when it is called using a custom domain. Routes are not re-mounted. This allows the same
+    /// `Rocket` to handle requests from both the default API Gateway URL and a custom domain, as long as its routes are
+    /// mounted at the custom domain's base path (if it has one).
     /// - `Include` - Includes the base bath in the URL. You must ensure that the `Rocket`'s routes have been
     /// mounted at the expected base path.
     /// - `Exclude` - Excludes the base bath from the URL. The URL processed by Rocket may not match the full
@@ -177,4 +403,563 @@ impl RocketHandlerBuilder {
         self.config.base_path_behaviour = setting;
         self
     }
+
+    /// Sets the API Gateway base path at which routes are re-mounted when the base path behaviour is `RemountAndInclude`,
+    /// or that is prepended to the path of every request when the base path behaviour is `IncludeFromFirstRequest`,
+    /// instead of determining it from the first request received. This has no effect for other base path behaviours.
+    ///
+    /// This is useful when the base path is known in advance, e.g. when the API is only called using the default API
+    /// Gateway URL with a fixed stage name. If requests are received with a different base path, they will not match
+    /// any re-mounted routes.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite()
+    ///     .lambda()
+    ///     .base_path("/Prod")
+    ///     .eager_client();
+    /// ```
+    pub fn base_path(mut self, base_path: &str) -> Self {
+        self.config.base_path = Some(base_path.to_owned());
+        self
+    }
+
+    /// Sets the number of leading path segments that make up the base path of requests made to an API Gateway custom
+    /// domain, e.g. `2` for a custom domain whose base path mapping is `api/v1`.
+    ///
+    /// By default, the base path of such requests is found by matching the request's API Gateway resource path against
+    /// the end of its path. Setting the number of segments avoids this, which is useful when the resource path does not
+    /// reliably match the request path. Requests with fewer path segments than this fail. This has no effect on
+    /// requests made to the default API Gateway URL, whose base path is always the stage, or from an Application Load
+    /// Balancer, which have no base path.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite().lambda().base_path_segments(2);
+    /// ```
+    pub fn base_path_segments(mut self, segments: usize) -> Self {
+        self.config.base_path_segments = Some(segments);
+        self
+    }
+
+    /// Sets the HTTP status code of the response returned when a Lambda request cannot be transformed
+    /// into a Rocket request, or a Rocket response cannot be transformed into a Lambda response.
+    /// The response body will be a short plain-text description of the error.
+    ///
+    /// By default, no response is returned and the error is propagated to the Lambda runtime instead,
+    /// which typically causes API Gateway to return a `502 Bad Gateway` with a generic message.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite().lambda().error_response(500);
+    /// ```
+    pub fn error_response(mut self, status: u16) -> Self {
+        self.config.error_response_status = Some(status);
+        self
+    }
+
+    /// Sets a function that is called for each request after it has been transformed into a Rocket
+    /// `LocalRequest`, and before it is dispatched to Rocket. This replaces any previously set function.
+    ///
+    /// The function receives the original Lambda request and the `LocalRequest`, so it can add headers
+    /// derived from the API Gateway request context (e.g. authorizer claims or stage variables),
+    /// or otherwise customise the request seen by Rocket.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::http::Header;
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite().lambda().on_request(|req, local_req| {
+    ///     let path = req.uri().path().to_owned();
+    ///     local_req.add_header(Header::new("X-Original-Path", path));
+    /// });
+    /// ```
+    pub fn on_request<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&Request, &mut LocalRequest) + Send + Sync + 'static,
+    {
+        self.config.on_request = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets a function that is called for each response after it has been transformed from a Rocket
+    /// response into a Lambda response. This replaces any previously set function.
+    ///
+    /// The function is also called for responses that are returned without dispatching the request
+    /// to Rocket, e.g. for health checks, CORS preflight requests, or when Rocket panics.
+    ///
+    /// This can be used to add headers (e.g. CORS or security headers) to every response when running
+    /// in Lambda, without attaching a fairing to the `Rocket`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite().lambda().on_response(|res| {
+    ///     res.headers_mut().insert(
+    ///         "access-control-allow-origin",
+    ///         "*".parse().unwrap(),
+    ///     );
+    /// });
+    /// ```
+    pub fn on_response<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&mut Response<Body>) + Send + Sync + 'static,
+    {
+        self.config.on_response = Some(Box::new(callback));
+        self
+    }
+
+    /// Creates the Rocket `Client` when the handler is created, rather than when the first request is received.
+    ///
+    /// By default, the `Client` is created when the first request is received. When the handler is created
+    /// during the Lambda function's initialization (e.g. by using [launch()](RocketHandlerBuilder::launch)), this moves
+    /// the cost of creating the `Client` out of the first invocation, reducing its latency.
+    ///
+    /// When the base path behaviour is `RemountAndInclude` (the default), this can only be used if a
+    /// [base_path](RocketHandlerBuilder::base_path) has been set, because otherwise the base path is determined from
+    /// the first request received.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::{BasePathBehaviour, RocketExt};
+    ///
+    /// let builder = rocket::ignite()
+    ///     .lambda()
+    ///     .base_path_behaviour(BasePathBehaviour::Include)
+    ///     .eager_client();
+    /// ```
+    pub fn eager_client(mut self) -> Self {
+        self.config.eager_client = true;
+        self
+    }
+
+    /// Sets a path that responds to all requests with an empty `200 OK` response, without dispatching the request to Rocket.
+    ///
+    /// This is useful for health checks and warming requests (e.g. from a scheduled CloudWatch event), which need to
+    /// invoke the function without doing any unnecessary work. The Rocket `Client` is still created if it has not been
+    /// already. The path is matched against the request path excluding any API Gateway base path.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite().lambda().health_check_path("/_health");
+    /// ```
+    pub fn health_check_path(mut self, path: &str) -> Self {
+        self.config.health_check_path = Some(path.to_owned());
+        self
+    }
+
+    /// Sets a path that responds with a JSON description of the request that would be dispatched to Rocket, without
+    /// dispatching it. The description contains the request's method, path, query string and headers, after any
+    /// base path, header and `on_request` processing has been applied.
+    ///
+    /// This can help diagnose requests that are not matched by the expected route, e.g. due to the base path. The path
+    /// is matched against the request path excluding any API Gateway base path.
+    ///
+    /// As the response includes every request header, e.g. `Authorization`, this should not be enabled in production.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite().lambda().debug_echo_path("/__lamb_debug");
+    /// ```
+    pub fn debug_echo_path(mut self, path: &str) -> Self {
+        self.config.debug_echo_path = Some(path.to_owned());
+        self
+    }
+
+    /// Sets the path of the requests dispatched to Rocket by
+    /// [handle_direct_invocation](crate::RocketHandler::handle_direct_invocation). The default is `/`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite().lambda().direct_invocation_path("/invoke");
+    /// ```
+    pub fn direct_invocation_path(mut self, path: &str) -> Self {
+        self.config.direct_invocation_path = path.to_owned();
+        self
+    }
+
+    /// Sets a function that transforms responses with the given HTTP status code. This replaces any
+    /// previously set function for the same status code.
+    ///
+    /// The function is called after the response has been transformed into a Lambda response, and after any
+    /// function set by [on_response](RocketHandlerBuilder::on_response). It is also called for responses that are
+    /// returned without dispatching the request to Rocket.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite().lambda().map_status(401, |mut res| {
+    ///     res.headers_mut()
+    ///         .insert("www-authenticate", "Bearer".parse().unwrap());
+    ///     res
+    /// });
+    /// ```
+    pub fn map_status<F>(mut self, status: u16, map: F) -> Self
+    where
+        F: Fn(Response<Body>) -> Response<Body> + Send + Sync + 'static,
+    {
+        self.config.status_mappings.insert(status, Box::new(map));
+        self
+    }
+
+    /// Logs a single line of JSON for each request using the [log](https://docs.rs/log) crate, which includes the
+    /// request's method, path, response status code, response body size and duration, and the AWS request ID.
+    ///
+    /// Requests that are handled successfully are logged at the `Info` level, and requests that fail are logged at the
+    /// `Error` level. This is disabled by default.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite().lambda().request_logging();
+    /// ```
+    pub fn request_logging(mut self) -> Self {
+        self.config.request_logging = true;
+        self
+    }
+
+    /// Writes the metrics of each request to stdout in CloudWatch
+    /// [Embedded Metric Format](https://docs.aws.amazon.com/AmazonCloudWatch/latest/monitoring/CloudWatch_Embedded_Metric_Format_Specification.html),
+    /// so that CloudWatch creates metrics from the function's logs without using X-Ray.
+    ///
+    /// The metrics are created in the given namespace with a `FunctionName` dimension. `2xx`, `3xx`, `4xx` and `5xx`
+    /// count responses by status code class, and `Latency` is the time taken to handle the request in milliseconds.
+    /// Requests that fail without a response are counted as `5xx`, as API Gateway returns a `502` response for them.
+    /// This is disabled by default.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite().lambda().emf_metrics("MyApi");
+    /// ```
+    pub fn emf_metrics(mut self, namespace: &str) -> Self {
+        self.config.emf_namespace = Some(namespace.to_owned());
+        self
+    }
+
+    /// Sets a header that is added to every response, containing the ID of the request. This makes it easier to correlate
+    /// a response received by a client with the logs of the request.
+    ///
+    /// For API Gateway requests, this is the API Gateway request ID. For Application Load Balancer requests, this is the
+    /// AWS request ID of the Lambda invocation. The header is conventionally named `X-Amzn-RequestId`
+    /// ([REQUEST_ID_HEADER](crate::REQUEST_ID_HEADER)), and replaces any header of the same name returned by Rocket.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `header_name` is not a valid HTTP header name.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::{RocketExt, REQUEST_ID_HEADER};
+    ///
+    /// let builder = rocket::ignite().lambda().echo_request_id(REQUEST_ID_HEADER);
+    /// ```
+    pub fn echo_request_id(mut self, header_name: &str) -> Self {
+        let header_name = HeaderName::from_bytes(header_name.as_bytes())
+            .unwrap_or_else(|_| panic!("'{}' is not a valid header name.", header_name));
+        self.config.request_id_header = Some(header_name);
+        self
+    }
+
+    /// Applies a function to the `Rocket` used to create the builder, e.g. to attach a fairing or mount extra routes
+    /// depending on the environment. The function is applied immediately, so the `Rocket` it returns is used when the
+    /// handler is created.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket::fairing::AdHoc;
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite().lambda().mutate_rocket(|rocket| {
+    ///     rocket.attach(AdHoc::on_response("Server", |_, res| {
+    ///         res.set_raw_header("Server", "rocket-lamb");
+    ///     }))
+    /// });
+    /// ```
+    pub fn mutate_rocket<F>(mut self, mutate: F) -> Self
+    where
+        F: FnOnce(Rocket) -> Rocket,
+    {
+        self.rocket = mutate(self.rocket);
+        self
+    }
+
+    /// Sets the `Host` header of every request dispatched to Rocket, replacing the `Host` header of the original request.
+    ///
+    /// By default, Rocket receives the `Host` header sent to API Gateway, which is the API Gateway domain name, i.e. either
+    /// the default `{api-id}.execute-api.{region}.amazonaws.com` domain or a custom domain. This is useful when the API is
+    /// served from a different domain (e.g. through a CloudFront distribution), so that absolute URLs generated by Rocket
+    /// use that domain.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite().lambda().force_host("api.example.com");
+    /// ```
+    pub fn force_host(mut self, host: &str) -> Self {
+        self.config.host = Some(host.to_owned());
+        self
+    }
+
+    /// Sets the scheme in the `X-Forwarded-Proto` header that is added to API Gateway requests which do not already have
+    /// one. The default is `https`, as API Gateway only accepts HTTPS requests.
+    ///
+    /// Rocket does not know the scheme that the client used, so routes that generate absolute URLs (e.g. for the
+    /// `Location` header of a redirect, or an OAuth callback URL) can use this header to choose the scheme. Overriding it
+    /// is useful when the API is served to clients over a different scheme, e.g. by a local emulator that uses HTTP.
+    /// The header is not added to requests from an Application Load Balancer, which sets it itself.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite().lambda().forwarded_proto("http");
+    /// ```
+    pub fn forwarded_proto(mut self, scheme: &str) -> Self {
+        self.config.forwarded_proto = scheme.to_owned();
+        self
+    }
+
+    /// Sets a function that determines the `ResponseType` of each response, e.g. based on a custom header.
+    ///
+    /// The `ResponseType` is determined by the first of the following that applies:
+    /// 1. The `ResponseType` set for the response's status code with [response_type_for_status](RocketHandlerBuilder::response_type_for_status)
+    /// 2. The `ResponseType` set by the response's [RESPONSE_TYPE_HEADER](crate::RESPONSE_TYPE_HEADER) header
+    /// 3. The `ResponseType` returned by this function, if it returns `Some`
+    /// 4. `Binary`, if the response is an attachment and [binary_attachments](RocketHandlerBuilder::binary_attachments) is enabled
+    /// 5. The `ResponseType` set for the response's Content-Type with [response_type](RocketHandlerBuilder::response_type)
+    /// 6. The [default_response_type](RocketHandlerBuilder::default_response_type)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::{ResponseType, RocketExt};
+    ///
+    /// let builder = rocket::ignite().lambda().response_type_resolver(|res| {
+    ///     if res.headers().contains("X-Binary") {
+    ///         Some(ResponseType::Binary)
+    ///     } else {
+    ///         None
+    ///     }
+    /// });
+    /// ```
+    pub fn response_type_resolver<F>(mut self, resolver: F) -> Self
+    where
+        F: Fn(&LocalResponse) -> Option<ResponseType> + Send + Sync + 'static,
+    {
+        self.config.response_type_resolver = Some(Box::new(resolver));
+        self
+    }
+
+    /// Responds with a `503 Service Unavailable` response (with a `Retry-After` header) instead of dispatching a request
+    /// to Rocket when less than `margin` remains before the Lambda invocation's deadline.
+    ///
+    /// This prevents requests from being started when the function is likely to time out before they complete, which
+    /// would make the invocation fail without a response. The margin should be at least as long as the slowest route
+    /// takes to respond. This is disabled by default.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    /// use std::time::Duration;
+    ///
+    /// let builder = rocket::ignite().lambda().respect_deadline(Duration::from_secs(1));
+    /// ```
+    pub fn respect_deadline(mut self, margin: Duration) -> Self {
+        self.config.deadline_margin = Some(margin);
+        self
+    }
+
+    /// Sets the binary media types configured on the API Gateway API (its `binaryMediaTypes` setting), so that responses
+    /// are encoded in the same way that API Gateway will deliver them.
+    ///
+    /// API Gateway only decodes a base-64 encoded response body into binary when the first media type in the request's
+    /// `Accept` header matches one of the API's binary media types. Any later media types are ignored, so e.g. a browser
+    /// request accepting `text/html,image/webp,*/*` does not match `image/*`. When this is set, responses with a
+    /// `ResponseType` of `Auto` are encoded as base-64 if the first media type in the request's `Accept` header matches
+    /// any of the given media types, regardless of the response's Content-Type. Otherwise, the response is encoded based
+    /// on its Content-Type as usual. Media types can be `*/*`, or end with `/*` to match any media type with that
+    /// top-level type.
+    ///
+    /// `media_types` values are treated case-insensitively.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite()
+    ///     .lambda()
+    ///     .api_gateway_binary_media_types(&["image/*", "application/octet-stream"]);
+    /// ```
+    pub fn api_gateway_binary_media_types(mut self, media_types: &[&str]) -> Self {
+        self.config
+            .binary_media_types
+            .extend(media_types.iter().map(|mt| mt.to_lowercase()));
+        self
+    }
+
+    /// Sets how a trailing slash at the end of the URL's path is handled before the request is passed to Rocket.
+    /// Rocket ignores trailing slashes when matching routes, but they are still visible to routes that use the
+    /// request's URI, e.g. when building relative redirects. As clients and API Gateway do not always agree on whether
+    /// a path has a trailing slash, this can be used to give Rocket a consistent path. The default is `TrailingSlash::Keep`.
+    ///
+    /// The possible values are:
+    /// - `Keep` - Passes the path to Rocket unchanged.
+    /// - `Strip` - Removes any trailing slash from the path, unless the path is `/`.
+    /// - `Add` - Adds a trailing slash to the path if it does not already end with one.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::{RocketExt, TrailingSlash};
+    ///
+    /// let builder = rocket::ignite()
+    ///     .lambda()
+    ///     .trailing_slash(TrailingSlash::Strip);
+    /// ```
+    pub fn trailing_slash(mut self, setting: TrailingSlash) -> Self {
+        self.config.trailing_slash = setting;
+        self
+    }
+
+    /// Removes the given headers from each request before it is passed to Rocket. Header names are matched
+    /// case-insensitively, and a name ending with `*` matches any header that starts with the preceding text.
+    ///
+    /// Headers that would otherwise be added by the handler (`X-Amzn-Trace-Id`, `X-Forwarded-Proto` and
+    /// `X-Forwarded-For`) are also omitted if they match. The `Host` header set with
+    /// [force_host](RocketHandlerBuilder::force_host) is always added.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite()
+    ///     .lambda()
+    ///     .strip_request_headers(&["X-Amzn-*", "Via"]);
+    /// ```
+    pub fn strip_request_headers(mut self, names: &[&str]) -> Self {
+        self.config
+            .stripped_request_headers
+            .extend(names.iter().map(|name| name.to_lowercase()));
+        self
+    }
+
+    /// Removes the given headers from each response returned by Rocket, e.g. the `Server` header that Rocket adds to
+    /// every response. Header names are matched case-insensitively, and a name ending with `*` matches any header that
+    /// starts with the preceding text.
+    ///
+    /// Headers added by the handler itself, e.g. the request ID header set with
+    /// [echo_request_id](RocketHandlerBuilder::echo_request_id) or headers added by an
+    /// [on_response](RocketHandlerBuilder::on_response) function, are not removed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite()
+    ///     .lambda()
+    ///     .strip_response_headers(&["Server"]);
+    /// ```
+    pub fn strip_response_headers(mut self, names: &[&str]) -> Self {
+        self.config
+            .stripped_response_headers
+            .extend(names.iter().map(|name| name.to_lowercase()));
+        self
+    }
+
+    /// Responds to CORS preflight requests that are not handled by a Rocket route. When an `OPTIONS` request with
+    /// `Origin` and `Access-Control-Request-Method` headers would otherwise return a 404 response, a 204 response is
+    /// returned instead, with `Access-Control-Allow-*` headers as configured by the given [CorsPreflight](crate::CorsPreflight).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::{CorsPreflight, RocketExt};
+    ///
+    /// let builder = rocket::ignite()
+    ///     .lambda()
+    ///     .cors_preflight(CorsPreflight::new().allowed_origins(&["https://example.com"]));
+    /// ```
+    pub fn cors_preflight(mut self, cors: CorsPreflight) -> Self {
+        self.config.cors_preflight = Some(cors);
+        self
+    }
+
+    /// Sets the Content-Type of responses that have a body but no Content-Type header. This Content-Type is also used
+    /// to determine the `ResponseType` of those responses. By default, no Content-Type is set.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite()
+    ///     .lambda()
+    ///     .default_content_type("application/octet-stream");
+    /// ```
+    pub fn default_content_type(mut self, content_type: &str) -> Self {
+        self.config.default_content_type = Some(content_type.to_owned());
+        self
+    }
+
+    /// Sets whether the Rocket `Client` used to dispatch requests tracks cookies. The default is `false`.
+    ///
+    /// A tracked client stores the cookies set by each response, and sends them with every later request handled by
+    /// the same Lambda container, in the same way as a browser would. As a container handles requests from many
+    /// different clients, **this causes cookies set for one user to be sent with other users' requests**, so it should
+    /// only be enabled in tests, or when every request to the function comes from a single trusted client.
+    ///
+    /// Cookies sent by the original client are passed to Rocket regardless of this setting.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let builder = rocket::ignite()
+    ///     .lambda()
+    ///     .tracked_client(true);
+    /// ```
+    pub fn tracked_client(mut self, setting: bool) -> Self {
+        self.config.tracked_client = setting;
+        self
+    }
 }
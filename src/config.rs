@@ -1,19 +1,120 @@
+use http::header::HeaderName;
+use lambda_http::{Body, Request, Response};
+use rocket::local::{LocalRequest, LocalResponse};
 use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::time::Duration;
 
 pub(crate) struct Config {
     pub(crate) default_response_type: ResponseType,
     pub(crate) response_types: HashMap<String, ResponseType>,
+    pub(crate) status_response_types: Vec<(RangeInclusive<u16>, ResponseType)>,
+    pub(crate) binary_media_types: Vec<String>,
+    pub(crate) text_fallback_to_binary: bool,
+    pub(crate) binary_attachments: bool,
+    pub(crate) max_response_size: Option<usize>,
+    pub(crate) truncate_response_size: Option<usize>,
+    pub(crate) max_request_size: Option<usize>,
+    pub(crate) decompress_requests: bool,
     pub(crate) base_path_behaviour: BasePathBehaviour,
+    pub(crate) base_path: Option<String>,
+    pub(crate) base_path_segments: Option<usize>,
+    pub(crate) eager_client: bool,
+    pub(crate) tracked_client: bool,
+    pub(crate) health_check_path: Option<String>,
+    pub(crate) debug_echo_path: Option<String>,
+    pub(crate) direct_invocation_path: String,
+    pub(crate) error_response_status: Option<u16>,
+    pub(crate) request_logging: bool,
+    pub(crate) emf_namespace: Option<String>,
+    pub(crate) request_id_header: Option<HeaderName>,
+    pub(crate) host: Option<String>,
+    pub(crate) forwarded_proto: String,
+    pub(crate) deadline_margin: Option<Duration>,
+    pub(crate) trailing_slash: TrailingSlash,
+    pub(crate) stripped_request_headers: Vec<String>,
+    pub(crate) stripped_response_headers: Vec<String>,
+    pub(crate) cors_preflight: Option<CorsPreflight>,
+    pub(crate) default_content_type: Option<String>,
+    pub(crate) on_request: Option<Box<dyn Fn(&Request, &mut LocalRequest) + Send + Sync>>,
+    pub(crate) on_response: Option<Box<dyn Fn(&mut Response<Body>) + Send + Sync>>,
+    pub(crate) status_mappings:
+        HashMap<u16, Box<dyn Fn(Response<Body>) -> Response<Body> + Send + Sync>>,
+    pub(crate) response_type_resolver:
+        Option<Box<dyn Fn(&LocalResponse) -> Option<ResponseType> + Send + Sync>>,
 }
 
+pub(crate) const DEFAULT_BINARY_CONTENT_TYPES: &[&str] = &[
+    "application/octet-stream",
+    "application/pdf",
+    "application/zip",
+    "application/gzip",
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "image/webp",
+    "image/bmp",
+    "image/tiff",
+    "image/x-icon",
+    "font/ttf",
+    "font/otf",
+    "font/woff",
+    "font/woff2",
+    "audio/mpeg",
+    "audio/ogg",
+    "video/mp4",
+    "video/webm",
+];
+
+/// The maximum size of a decompressed request body when no maximum request size has been set.
+pub(crate) const DEFAULT_MAX_DECOMPRESSED_REQUEST_SIZE: usize = 10 * 1024 * 1024;
+
+/// The name of a response header that a route can set to override the `ResponseType` of its response. The value can
+/// be `auto`, `text` or `binary` (case-insensitive), and takes precedence over all other response type configuration
+/// except [response_type_for_status](crate::RocketHandlerBuilder::response_type_for_status).
+/// The header is removed from the response before it is returned to API Gateway.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket::http::ContentType;
+/// use rocket::response::{content::Content, Responder};
+/// use rocket::{Request, Response};
+/// use rocket_lamb::RESPONSE_TYPE_HEADER;
+///
+/// struct AsBinary(Vec<u8>);
+///
+/// impl<'r> Responder<'r> for AsBinary {
+///     fn respond_to(self, req: &Request) -> rocket::response::Result<'r> {
+///         Response::build_from(Content(ContentType::Plain, self.0).respond_to(req)?)
+///             .raw_header(RESPONSE_TYPE_HEADER, "binary")
+///             .ok()
+///     }
+/// }
+/// ```
+pub const RESPONSE_TYPE_HEADER: &str = "X-Lambda-Response-Type";
+
+/// The name of the response header that is set to `true` when a response body has been truncated by
+/// [truncate_oversized_responses](crate::RocketHandlerBuilder::truncate_oversized_responses).
+pub const TRUNCATED_HEADER: &str = "X-Lamb-Truncated";
+
+/// The conventional name of the response header containing the ID of the request, which can be passed to
+/// [echo_request_id](crate::RocketHandlerBuilder::echo_request_id).
+pub const REQUEST_ID_HEADER: &str = "X-Amzn-RequestId";
+
 /// Determines how to encode response content. The default is `Auto`.
-#[derive(PartialEq, Copy, Clone, Debug)]
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
 pub enum ResponseType {
-    /// Attempts to encode response content as a UTF-8 string. If it is not valid UTF-8, it is instead encoded as base-64.
+    /// Chooses the encoding based on the response's Content-Type. Textual types (`text/*`, `application/json`,
+    /// `application/xml`, `application/javascript`, `*+json` and `*+xml`) are encoded as a UTF-8 string, and
+    /// all other types are encoded as base-64. Responses without a Content-Type are encoded as a UTF-8 string.
+    /// Content that is not valid UTF-8 is always encoded as base-64. If API Gateway binary media types have been set with
+    /// [api_gateway_binary_media_types](crate::RocketHandlerBuilder::api_gateway_binary_media_types), content is also
+    /// encoded as base-64 when the first media type in the request's `Accept` header matches one of them.
     Auto,
     /// Encodes response content as a UTF-8 string.
     Text,
-    /// Encodes response content as base-64.
+    /// Encodes response content as base-64. Empty response content is also marked as base-64 encoded.
     Binary,
 }
 
@@ -24,6 +125,17 @@ pub enum BasePathBehaviour {
     /// Includes the base bath in the URL. The first request received will be used to determine
     /// the base path, and all mounted routes will be cloned and re-mounted at the base path.
     RemountAndInclude,
+    /// Includes the base bath in the URL. The first request received will be used to determine
+    /// the base path, which is then prepended to the path of every request. Routes are not re-mounted,
+    /// so you must ensure that the `Rocket` handles the base path, e.g. by mounting routes at the
+    /// expected base path or by using a fairing that rewrites request URIs. This avoids the cost of
+    /// cloning every route, and any collisions between the original and re-mounted routes.
+    IncludeFromFirstRequest,
+    /// Excludes the stage from the URL when the API is called using the default API Gateway URL, but
+    /// includes the base path when it is called using a custom domain. Routes are not re-mounted. This
+    /// allows the same `Rocket` to handle requests from both the default API Gateway URL and a custom
+    /// domain, as long as its routes are mounted at the custom domain's base path (if it has one).
+    ExcludeStage,
     /// Includes the base bath in the URL. You must ensure that the `Rocket`'s routes have been
     /// mounted at the expected base path.
     Include,
@@ -33,12 +145,142 @@ pub enum BasePathBehaviour {
     Exclude,
 }
 
+/// Determines how a trailing slash at the end of the URL's path is handled before the request is passed to Rocket.
+/// The default is `Keep`.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum TrailingSlash {
+    /// Passes the path to Rocket unchanged.
+    Keep,
+    /// Removes any trailing slash from the path, unless the path is `/`.
+    Strip,
+    /// Adds a trailing slash to the path if it does not already end with one.
+    Add,
+}
+
+/// Configures the response to CORS preflight requests that are not handled by a Rocket route.
+/// See [cors_preflight](crate::RocketHandlerBuilder::cors_preflight).
+///
+/// By default, requests from any origin are allowed, using the `GET`, `HEAD`, `POST`, `PUT`, `PATCH` and `DELETE`
+/// methods and any request headers.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket_lamb::CorsPreflight;
+/// use std::time::Duration;
+///
+/// let cors = CorsPreflight::new()
+///     .allowed_origins(&["https://example.com"])
+///     .allowed_methods(&["GET", "POST"])
+///     .allowed_headers(&["Content-Type", "Authorization"])
+///     .max_age(Duration::from_secs(3600));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CorsPreflight {
+    pub(crate) allowed_origins: Vec<String>,
+    pub(crate) allowed_methods: Vec<String>,
+    pub(crate) allowed_headers: Vec<String>,
+    pub(crate) max_age: Option<Duration>,
+}
+
+impl CorsPreflight {
+    /// Creates a new `CorsPreflight` with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the origins that are allowed to make cross-origin requests. `*` allows any origin.
+    /// Preflight requests from other origins are passed to Rocket's 404 catcher as usual.
+    pub fn allowed_origins(mut self, origins: &[&str]) -> Self {
+        self.allowed_origins = origins.iter().map(|o| (*o).to_owned()).collect();
+        self
+    }
+
+    /// Sets the methods that are returned in the `Access-Control-Allow-Methods` header.
+    pub fn allowed_methods(mut self, methods: &[&str]) -> Self {
+        self.allowed_methods = methods.iter().map(|m| (*m).to_owned()).collect();
+        self
+    }
+
+    /// Sets the request headers that are returned in the `Access-Control-Allow-Headers` header. `*` allows any header.
+    pub fn allowed_headers(mut self, headers: &[&str]) -> Self {
+        self.allowed_headers = headers.iter().map(|h| (*h).to_owned()).collect();
+        self
+    }
+
+    /// Sets how long the results of a preflight request may be cached, which is returned in the
+    /// `Access-Control-Max-Age` header. By default, the header is not set.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+}
+
+impl Default for CorsPreflight {
+    fn default() -> Self {
+        CorsPreflight {
+            allowed_origins: vec!["*".to_owned()],
+            allowed_methods: ["GET", "HEAD", "POST", "PUT", "PATCH", "DELETE"]
+                .iter()
+                .map(|m| (*m).to_owned())
+                .collect(),
+            allowed_headers: vec!["*".to_owned()],
+            max_age: None,
+        }
+    }
+}
+
+impl Config {
+    pub(crate) fn response_type_for(&self, content_type: &str) -> ResponseType {
+        let content_type = content_type.to_lowercase();
+        self.response_types
+            .get(&content_type)
+            .or_else(|| {
+                let top_level_type = content_type.split('/').next().unwrap_or_default();
+                self.response_types.get(&format!("{}/*", top_level_type))
+            })
+            .copied()
+            .unwrap_or(self.default_response_type)
+    }
+}
+
 impl Default for Config {
     fn default() -> Config {
         Config {
             default_response_type: ResponseType::Auto,
             response_types: HashMap::new(),
+            status_response_types: Vec::new(),
+            binary_media_types: Vec::new(),
+            text_fallback_to_binary: false,
+            binary_attachments: false,
+            max_response_size: None,
+            truncate_response_size: None,
+            max_request_size: None,
+            decompress_requests: false,
             base_path_behaviour: BasePathBehaviour::RemountAndInclude,
+            base_path: None,
+            base_path_segments: None,
+            eager_client: false,
+            tracked_client: false,
+            health_check_path: None,
+            debug_echo_path: None,
+            direct_invocation_path: "/".to_owned(),
+            error_response_status: None,
+            request_logging: false,
+            emf_namespace: None,
+            request_id_header: None,
+            host: None,
+            forwarded_proto: "https".to_owned(),
+            deadline_margin: None,
+            trailing_slash: TrailingSlash::Keep,
+            stripped_request_headers: Vec::new(),
+            stripped_response_headers: Vec::new(),
+            cors_preflight: None,
+            default_content_type: None,
+            on_request: None,
+            on_response: None,
+            status_mappings: HashMap::new(),
+            response_type_resolver: None,
         }
     }
 }
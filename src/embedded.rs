@@ -0,0 +1,81 @@
+use rocket::handler::{Handler, Outcome};
+use rocket::http::uri::{Segments, Uri};
+use rocket::http::{ContentType, Method, Status};
+use rocket::response::content::Content;
+use rocket::{Data, Request, Route};
+
+/// A custom handler for serving files that are embedded in the binary, e.g. using [include_bytes!](std::include_bytes).
+///
+/// Serving files from memory avoids reading them from disk on every request, which can be slow in AWS Lambda.
+/// The Content-Type of each response is determined by the file's extension, so binary files (e.g. images) are
+/// base-64 encoded when the response type is `Auto`.
+///
+/// Requests for files that have not been embedded are forwarded, so they can be handled by other routes.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket_lamb::EmbeddedFiles;
+///
+/// const FILES: &[(&str, &[u8])] = &[
+///     ("hello.txt", b"Hello, world!"),
+///     ("css/site.css", b"body { margin: 0; }"),
+/// ];
+///
+/// let rocket = rocket::ignite().mount("/static", EmbeddedFiles::new(FILES));
+/// ```
+#[derive(Clone)]
+pub struct EmbeddedFiles {
+    files: &'static [(&'static str, &'static [u8])],
+    rank: isize,
+}
+
+impl EmbeddedFiles {
+    /// The default rank used by `EmbeddedFiles` routes, which is the same as Rocket's `StaticFiles`.
+    pub const DEFAULT_RANK: isize = 10;
+
+    /// Creates a new `EmbeddedFiles` that serves the given files, which are pairs of paths (relative to the
+    /// mount point, without a leading `/`) and contents.
+    pub fn new(files: &'static [(&'static str, &'static [u8])]) -> Self {
+        EmbeddedFiles {
+            files,
+            rank: Self::DEFAULT_RANK,
+        }
+    }
+
+    /// Sets the rank of the routes emitted by this `EmbeddedFiles`.
+    pub fn rank(mut self, rank: isize) -> Self {
+        self.rank = rank;
+        self
+    }
+}
+
+impl From<EmbeddedFiles> for Vec<Route> {
+    fn from(files: EmbeddedFiles) -> Vec<Route> {
+        vec![Route::ranked(files.rank, Method::Get, "/<path..>", files)]
+    }
+}
+
+impl Handler for EmbeddedFiles {
+    fn handle<'r>(&self, req: &'r Request<'_>, data: Data) -> Outcome<'r> {
+        let path = match req.get_segments::<Segments>(0).and_then(|res| res.ok()) {
+            Some(segments) => segments.collect::<Vec<&str>>().join("/"),
+            None => return Outcome::failure(Status::NotFound),
+        };
+        let path = match Uri::percent_decode(path.as_bytes()) {
+            Ok(path) => path,
+            Err(_) => return Outcome::failure(Status::BadRequest),
+        };
+        match self.files.iter().find(|(name, _)| *name == path) {
+            Some((name, contents)) => {
+                let content_type = name
+                    .rsplit('.')
+                    .next()
+                    .and_then(ContentType::from_extension)
+                    .unwrap_or(ContentType::Binary);
+                Outcome::from(req, Content(content_type, *contents))
+            }
+            None => Outcome::forward(data),
+        }
+    }
+}
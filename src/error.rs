@@ -1,11 +1,36 @@
-#[derive(Debug, Fail)]
-pub(crate) enum RocketLambError {
-    #[fail(display = "could not transform request: {}", 0)]
+use std::fmt;
+
+/// An error that prevented a [RocketHandler](crate::RocketHandler) from handling an event.
+///
+/// This is returned by [try_handle](crate::RocketHandler::try_handle). When an error response has been configured with
+/// [error_response](crate::RocketHandlerBuilder::error_response), a response is returned instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RocketLambError {
+    /// The event could not be transformed into a request for Rocket, e.g. because it had an invalid header value.
     InvalidRequest(String),
-    #[fail(display = "could not transform response: {}", 0)]
+    /// Rocket's response could not be transformed into a response for Lambda, e.g. because its body was too large.
     InvalidResponse(String),
+    /// The event was not from API Gateway or an Application Load Balancer.
+    UnsupportedEvent(String),
+    /// The `Rocket` could not be launched, e.g. because it has colliding routes.
+    LaunchFailed(String),
+}
+
+impl fmt::Display for RocketLambError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RocketLambError::InvalidRequest(e) => write!(f, "could not transform request: {}", e),
+            RocketLambError::InvalidResponse(e) => {
+                write!(f, "could not transform response: {}", e)
+            }
+            RocketLambError::UnsupportedEvent(e) => write!(f, "unsupported event: {}", e),
+            RocketLambError::LaunchFailed(e) => write!(f, "could not launch Rocket: {}", e),
+        }
+    }
 }
 
+impl std::error::Error for RocketLambError {}
+
 macro_rules! invalid_request {
     ($($arg:tt)*) => (RocketLambError::InvalidRequest(format!($($arg)*)))
 }
@@ -0,0 +1,458 @@
+use lambda_http::request::RequestContext;
+use lambda_http::StrMap;
+use lambda_runtime::Context;
+use rocket::http::Status;
+use rocket::request::{self, FromRequest, Request};
+use rocket::Outcome;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::ops::Deref;
+
+/// Data about the Lambda invocation, stored in the local cache of each request dispatched to Rocket.
+pub(crate) struct LambdaRequestState {
+    pub(crate) context: Context,
+    pub(crate) request_context: RequestContext,
+    pub(crate) stage_variables: StrMap,
+    pub(crate) custom_domain: bool,
+}
+
+impl LambdaRequestState {
+    fn from_request<'a>(request: &'a Request) -> Option<&'a LambdaRequestState> {
+        request.local_cache(|| None::<LambdaRequestState>).as_ref()
+    }
+}
+
+/// A request guard that provides the Lambda [Context](lambda_runtime::Context) of the current invocation,
+/// e.g. to get the AWS request ID or the function ARN.
+///
+/// This guard fails with a `500 Internal Server Error` if the request was not received via a [RocketHandler](crate::RocketHandler).
+///
+/// # Example
+///
+/// ```rust
+/// #![feature(proc_macro_hygiene, decl_macro)]
+///
+/// #[macro_use] extern crate rocket;
+/// use rocket_lamb::LambdaContext;
+///
+/// #[get("/")]
+/// fn request_id(context: LambdaContext) -> String {
+///     context.aws_request_id.clone()
+/// }
+/// # fn main() {}
+/// ```
+pub struct LambdaContext<'a>(&'a Context);
+
+impl<'a, 'r> FromRequest<'a, 'r> for LambdaContext<'a> {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        match LambdaRequestState::from_request(request) {
+            Some(state) => Outcome::Success(LambdaContext(&state.context)),
+            None => Outcome::Failure((Status::InternalServerError, ())),
+        }
+    }
+}
+
+impl Deref for LambdaContext<'_> {
+    type Target = Context;
+
+    fn deref(&self) -> &Context {
+        self.0
+    }
+}
+
+/// A request guard that provides the claims of an API Gateway authorizer.
+///
+/// For a Cognito User Pool authorizer, these are the claims of the user's token, e.g. `sub` and `email`.
+/// For a Lambda authorizer, these are the values of the `context` returned by the authorizer, along with
+/// its `principalId`.
+///
+/// This guard forwards if the request has no authorizer data, e.g. if the API Gateway method has no authorizer,
+/// or the request came from an Application Load Balancer. Use `Option<AuthorizerClaims>` to handle these requests
+/// within the same route.
+///
+/// # Example
+///
+/// ```rust
+/// #![feature(proc_macro_hygiene, decl_macro)]
+///
+/// #[macro_use] extern crate rocket;
+/// use rocket_lamb::AuthorizerClaims;
+///
+/// #[get("/")]
+/// fn user_id(claims: AuthorizerClaims) -> Option<String> {
+///     claims.get("sub")?.as_str().map(str::to_owned)
+/// }
+/// # fn main() {}
+/// ```
+pub struct AuthorizerClaims(HashMap<String, Value>);
+
+impl<'a, 'r> FromRequest<'a, 'r> for AuthorizerClaims {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        let state = match LambdaRequestState::from_request(request) {
+            Some(state) => state,
+            None => return Outcome::Failure((Status::InternalServerError, ())),
+        };
+        let authorizer = match &state.request_context {
+            RequestContext::ApiGateway { authorizer, .. } => authorizer,
+            RequestContext::Alb { .. } => return Outcome::Forward(()),
+        };
+        match authorizer.get("claims") {
+            Some(Value::Object(claims)) => Outcome::Success(AuthorizerClaims(
+                claims.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+            )),
+            _ if authorizer.is_empty() => Outcome::Forward(()),
+            _ => Outcome::Success(AuthorizerClaims(authorizer.clone())),
+        }
+    }
+}
+
+impl AuthorizerClaims {
+    /// Consumes the `AuthorizerClaims`, returning the wrapped map of claims.
+    pub fn into_inner(self) -> HashMap<String, Value> {
+        self.0
+    }
+}
+
+impl Deref for AuthorizerClaims {
+    type Target = HashMap<String, Value>;
+
+    fn deref(&self) -> &HashMap<String, Value> {
+        &self.0
+    }
+}
+
+/// A request guard that provides the API Gateway stage variables of the current request.
+///
+/// If the request has no stage variables (e.g. because it came from an Application Load Balancer), this
+/// guard succeeds with an empty map.
+///
+/// # Example
+///
+/// ```rust
+/// #![feature(proc_macro_hygiene, decl_macro)]
+///
+/// #[macro_use] extern crate rocket;
+/// use rocket_lamb::StageVariables;
+///
+/// #[get("/")]
+/// fn backend(stage_variables: StageVariables) -> Option<String> {
+///     stage_variables.get("backend").map(str::to_owned)
+/// }
+/// # fn main() {}
+/// ```
+pub struct StageVariables(StrMap);
+
+impl<'a, 'r> FromRequest<'a, 'r> for StageVariables {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        match LambdaRequestState::from_request(request) {
+            Some(state) => Outcome::Success(StageVariables(state.stage_variables.clone())),
+            None => Outcome::Failure((Status::InternalServerError, ())),
+        }
+    }
+}
+
+impl StageVariables {
+    /// Consumes the `StageVariables`, returning the wrapped map of stage variables.
+    pub fn into_inner(self) -> StrMap {
+        self.0
+    }
+}
+
+impl Deref for StageVariables {
+    type Target = StrMap;
+
+    fn deref(&self) -> &StrMap {
+        &self.0
+    }
+}
+
+/// A request guard that provides the request context of the current request, as deserialized by `lambda_http`.
+///
+/// This is an escape hatch for accessing parts of the request context that are not otherwise exposed by this crate.
+/// Only the fields modelled by [RequestContext](lambda_http::request::RequestContext) are available.
+///
+/// # Example
+///
+/// ```rust
+/// #![feature(proc_macro_hygiene, decl_macro)]
+///
+/// #[macro_use] extern crate rocket;
+/// use lambda_http::request::RequestContext;
+/// use rocket_lamb::RawApiGatewayContext;
+///
+/// #[get("/")]
+/// fn api_id(context: RawApiGatewayContext) -> Option<String> {
+///     match &*context {
+///         RequestContext::ApiGateway { api_id, .. } => Some(api_id.clone()),
+///         RequestContext::Alb { .. } => None,
+///     }
+/// }
+/// # fn main() {}
+/// ```
+pub struct RawApiGatewayContext<'a>(&'a RequestContext);
+
+impl<'a, 'r> FromRequest<'a, 'r> for RawApiGatewayContext<'a> {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        match LambdaRequestState::from_request(request) {
+            Some(state) => Outcome::Success(RawApiGatewayContext(&state.request_context)),
+            None => Outcome::Failure((Status::InternalServerError, ())),
+        }
+    }
+}
+
+impl Deref for RawApiGatewayContext<'_> {
+    type Target = RequestContext;
+
+    fn deref(&self) -> &RequestContext {
+        self.0
+    }
+}
+
+/// A request guard that provides the AWS X-Ray trace header of the current request, e.g. to propagate it to
+/// requests made to other services.
+///
+/// Requests dispatched to Rocket by a [RocketHandler](crate::RocketHandler) have their `X-Amzn-Trace-Id` header set
+/// to the trace ID of the Lambda invocation (or the `_X_AMZN_TRACE_ID` environment variable) when one is available,
+/// so this guard also works for requests that did not arrive with the header.
+///
+/// This guard forwards if the request has no `X-Amzn-Trace-Id` header.
+///
+/// # Example
+///
+/// ```rust
+/// #![feature(proc_macro_hygiene, decl_macro)]
+///
+/// #[macro_use] extern crate rocket;
+/// use rocket_lamb::TraceId;
+///
+/// #[get("/")]
+/// fn trace_id(trace_id: TraceId) -> String {
+///     trace_id.to_string()
+/// }
+/// # fn main() {}
+/// ```
+pub struct TraceId(String);
+
+impl<'a, 'r> FromRequest<'a, 'r> for TraceId {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        match request.headers().get_one("X-Amzn-Trace-Id") {
+            Some(trace_id) => Outcome::Success(TraceId(trace_id.to_owned())),
+            None => Outcome::Forward(()),
+        }
+    }
+}
+
+impl TraceId {
+    /// Consumes the `TraceId`, returning the wrapped trace header value.
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl Deref for TraceId {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// A request guard that indicates whether the current request was made to an API Gateway custom domain, rather than
+/// to the API's default `execute-api` URL, e.g. to generate links that include the stage only when it is required.
+///
+/// Requests from an Application Load Balancer are not considered to be made to a custom domain.
+///
+/// This guard fails with a `500 Internal Server Error` if the request was not received via a [RocketHandler](crate::RocketHandler).
+///
+/// # Example
+///
+/// ```rust
+/// #![feature(proc_macro_hygiene, decl_macro)]
+///
+/// #[macro_use] extern crate rocket;
+/// use rocket_lamb::IsCustomDomain;
+///
+/// #[get("/")]
+/// fn home_link(custom_domain: IsCustomDomain) -> &'static str {
+///     if custom_domain.0 {
+///         "/"
+///     } else {
+///         "/Prod/"
+///     }
+/// }
+/// # fn main() {}
+/// ```
+pub struct IsCustomDomain(pub bool);
+
+impl<'a, 'r> FromRequest<'a, 'r> for IsCustomDomain {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        match LambdaRequestState::from_request(request) {
+            Some(state) => Outcome::Success(IsCustomDomain(state.custom_domain)),
+            None => Outcome::Failure((Status::InternalServerError, ())),
+        }
+    }
+}
+
+/// A request guard that provides the request context of the current request as JSON, using the same field names
+/// as the Lambda event, e.g. `requestId` and `identity.sourceIp` for API Gateway requests, or `elb.targetGroupArn`
+/// for requests from an Application Load Balancer.
+///
+/// The JSON is built from the request context as deserialized by `lambda_http`, so only the fields modelled by
+/// [RequestContext](lambda_http::request::RequestContext) are included. Other fields of the original event (e.g.
+/// `domainPrefix` or `extendedRequestId`) are not available.
+///
+/// This guard fails with a `500 Internal Server Error` if the request was not received via a [RocketHandler](crate::RocketHandler).
+///
+/// # Example
+///
+/// ```rust
+/// #![feature(proc_macro_hygiene, decl_macro)]
+///
+/// #[macro_use] extern crate rocket;
+/// use rocket_lamb::RequestContextJson;
+///
+/// #[get("/")]
+/// fn source_ip(context: RequestContextJson) -> Option<String> {
+///     context["identity"]["sourceIp"].as_str().map(str::to_owned)
+/// }
+/// # fn main() {}
+/// ```
+pub struct RequestContextJson(Value);
+
+impl<'a, 'r> FromRequest<'a, 'r> for RequestContextJson {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        match LambdaRequestState::from_request(request) {
+            Some(state) => Outcome::Success(RequestContextJson(request_context_json(
+                &state.request_context,
+            ))),
+            None => Outcome::Failure((Status::InternalServerError, ())),
+        }
+    }
+}
+
+impl RequestContextJson {
+    /// Consumes the `RequestContextJson`, returning the wrapped JSON value.
+    pub fn into_inner(self) -> Value {
+        self.0
+    }
+}
+
+impl Deref for RequestContextJson {
+    type Target = Value;
+
+    fn deref(&self) -> &Value {
+        &self.0
+    }
+}
+
+fn request_context_json(request_context: &RequestContext) -> Value {
+    match request_context {
+        RequestContext::ApiGateway {
+            account_id,
+            resource_id,
+            stage,
+            request_id,
+            resource_path,
+            http_method,
+            authorizer,
+            api_id,
+            identity,
+        } => json!({
+            "accountId": account_id,
+            "resourceId": resource_id,
+            "stage": stage,
+            "requestId": request_id,
+            "resourcePath": resource_path,
+            "httpMethod": http_method.to_string(),
+            "authorizer": authorizer,
+            "apiId": api_id,
+            "identity": {
+                "sourceIp": identity.source_ip,
+                "cognitoIdentityId": identity.cognito_identity_id,
+                "cognitoIdentityPoolId": identity.cognito_identity_pool_id,
+                "cognitoAuthenticationProvider": identity.cognito_authentication_provider,
+                "cognitoAuthenticationType": identity.cognito_authentication_type,
+                "accountId": identity.account_id,
+                "caller": identity.caller,
+                "apiKey": identity.api_key,
+                "accessKey": identity.access_key,
+                "user": identity.user,
+                "userAgent": identity.user_agent,
+                "userArn": identity.user_arn,
+            },
+        }),
+        RequestContext::Alb { elb } => json!({
+            "elb": {
+                "targetGroupArn": elb.target_group_arn,
+            },
+        }),
+    }
+}
+
+/// A request guard that provides the API Gateway resource path template that matched the current request, e.g.
+/// `/users/{id}` or `/{proxy+}`, which can be used to group requests for logging or metrics without including the
+/// values of path parameters.
+///
+/// This guard forwards if the request came from an Application Load Balancer, which has no resource path.
+///
+/// # Example
+///
+/// ```rust
+/// #![feature(proc_macro_hygiene, decl_macro)]
+///
+/// #[macro_use] extern crate rocket;
+/// use rocket_lamb::ResourcePath;
+///
+/// #[get("/users/<id>")]
+/// fn user(id: u32, resource_path: ResourcePath) -> String {
+///     format!("User {} matched {}", id, &*resource_path)
+/// }
+/// # fn main() {}
+/// ```
+pub struct ResourcePath(String);
+
+impl<'a, 'r> FromRequest<'a, 'r> for ResourcePath {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        let state = match LambdaRequestState::from_request(request) {
+            Some(state) => state,
+            None => return Outcome::Failure((Status::InternalServerError, ())),
+        };
+        match &state.request_context {
+            RequestContext::ApiGateway { resource_path, .. } => {
+                Outcome::Success(ResourcePath(resource_path.clone()))
+            }
+            RequestContext::Alb { .. } => Outcome::Forward(()),
+        }
+    }
+}
+
+impl ResourcePath {
+    /// Consumes the `ResourcePath`, returning the wrapped resource path.
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl Deref for ResourcePath {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
@@ -1,118 +1,669 @@
 use crate::config::*;
 use crate::error::RocketLambError;
+use crate::guards::LambdaRequestState;
+use crate::logging::RequestLog;
+use crate::metrics::EmfMetrics;
 use crate::request_ext::RequestExt as _;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use http::header::{
+    HeaderValue, ACCEPT, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS,
+    ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_MAX_AGE, ACCESS_CONTROL_REQUEST_METHOD, ALLOW,
+    CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, HOST, ORIGIN, RETRY_AFTER, VARY,
+};
+use lambda_http::request::RequestContext;
 use lambda_http::{Body, Handler, Request, RequestExt, Response};
 use lambda_runtime::{error::HandlerError, Context};
-use rocket::http::{uri::Uri, Header};
+use log::{debug, error, warn};
+use once_cell::sync::OnceCell;
+use rocket::http::uri::{Origin, Uri};
+use rocket::http::{Header, Method, Status};
 use rocket::local::{Client, LocalRequest, LocalResponse};
 use rocket::{Rocket, Route};
-use std::mem;
+use serde_json::{json, Value};
+use std::env;
+use std::io::{self, Read};
+use std::net::SocketAddr;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 /// A Lambda handler for API Gateway events that processes requests using a [Rocket](rocket::Rocket) instance.
+///
+/// A `RocketHandler` is created and configured using a [RocketHandlerBuilder](crate::RocketHandlerBuilder),
+/// which can be obtained by calling [lambda()](crate::RocketExt::lambda) on a `Rocket`.
+///
+/// # Example
+///
+/// ```rust
+/// use rocket_lamb::{ResponseType, RocketExt};
+///
+/// let handler = rocket::ignite()
+///     .lambda()
+///     .default_response_type(ResponseType::Text)
+///     .response_type("image/png", ResponseType::Binary)
+///     .into_handler();
+/// ```
 pub struct RocketHandler {
-    pub(super) client: LazyClient,
+    pub(super) rocket: Mutex<Option<Rocket>>,
+    pub(super) client: OnceCell<Result<Client, String>>,
+    pub(super) detected_base_path: OnceCell<String>,
     pub(super) config: Config,
 }
 
-pub(super) enum LazyClient {
-    Placeholder,
-    Uninitialized(Rocket),
-    Ready(Client),
+impl Handler<Response<Body>> for RocketHandler {
+    fn run(&mut self, req: Request, ctx: Context) -> Result<Response<Body>, HandlerError> {
+        self.handle(req, ctx)
+    }
 }
 
-impl Handler<Response<Body>> for RocketHandler {
-    fn run(&mut self, req: Request, _ctx: Context) -> Result<Response<Body>, HandlerError> {
-        self.ensure_client_ready(&req);
-        self.process_request(req)
+impl RocketHandler {
+    /// Handles a request in the same way as [run](lambda_http::Handler::run), but without requiring a mutable reference
+    /// to the `RocketHandler`. This allows a single `RocketHandler` to be shared between threads, e.g. using an `Arc`.
+    ///
+    /// If the Rocket `Client` has not yet been created, it is created when handling the first request. Any other requests
+    /// received while it is being created will wait for it to be ready.
+    ///
+    /// Unlike [launch](crate::RocketHandlerBuilder::launch), this does not start the Lambda runtime, so it can be used to
+    /// handle a single event from within your own runtime, or to test a `Rocket` without writing request files to disk.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use lambda_runtime::Context;
+    /// use rocket_lamb::RocketExt;
+    /// use std::sync::Arc;
+    ///
+    /// let handler = Arc::new(rocket::ignite().lambda().into_handler());
+    ///
+    /// let req = lambda_http::request::from_str(r#"{
+    ///     "requestContext": { "elb": { "targetGroupArn": "arn:aws:elasticloadbalancing:eu-west-1:123456789123:targetgroup/example/0123456789abcdef" } },
+    ///     "path": "/",
+    ///     "httpMethod": "GET",
+    ///     "headers": { "Host": "example.com" },
+    ///     "queryStringParameters": {},
+    ///     "body": "",
+    ///     "isBase64Encoded": false
+    /// }"#).unwrap();
+    /// let res = handler.handle(req, Context::default()).unwrap();
+    /// assert_eq!(res.status(), 404);
+    /// ```
+    pub fn handle(&self, req: Request, ctx: Context) -> Result<Response<Body>, HandlerError> {
+        self.try_handle(req, ctx)
             .map_err(failure::Error::from)
             .map_err(failure::Error::into)
     }
-}
 
-impl RocketHandler {
-    fn ensure_client_ready(&mut self, req: &Request) {
-        match self.client {
-            ref mut lazy_client @ LazyClient::Uninitialized(_) => {
-                let uninitialized_client = mem::replace(lazy_client, LazyClient::Placeholder);
-                let mut rocket = match uninitialized_client {
-                    LazyClient::Uninitialized(rocket) => rocket,
-                    _ => unreachable!("LazyClient must be uninitialized at this point."),
-                };
+    /// Handles a request in the same way as [handle](RocketHandler::handle), but returns a
+    /// [RocketLambError](crate::RocketLambError) if the request could not be handled. This allows the cause of the
+    /// failure to be inspected when calling the handler directly, rather than via the Lambda runtime.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use lambda_http::{Body, Request};
+    /// use lambda_runtime::Context;
+    /// use rocket_lamb::{RocketExt, RocketLambError};
+    ///
+    /// let handler = rocket::ignite().lambda().into_handler();
+    ///
+    /// match handler.try_handle(Request::new(Body::Empty), Context::default()) {
+    ///     Err(RocketLambError::UnsupportedEvent(e)) => eprintln!("Unsupported event: {}", e),
+    ///     Err(e) => eprintln!("Failed to handle request: {}", e),
+    ///     Ok(res) => println!("Status: {}", res.status()),
+    /// }
+    /// ```
+    pub fn try_handle(
+        &self,
+        req: Request,
+        ctx: Context,
+    ) -> Result<Response<Body>, RocketLambError> {
+        // The base path is computed once per request, as finding it can be relatively expensive
+        let base_path = req.base_path(self.config.base_path_segments);
+        if let Ok(base_path) = &base_path {
+            self.ensure_client_ready(base_path);
+        }
+        let request_log = if self.config.request_logging {
+            let path = match &base_path {
+                Ok(base_path) => self.get_path_and_query(&req, base_path),
+                Err(_) => req.uri().path().to_owned(),
+            };
+            Some(RequestLog::new(&req, path, &ctx))
+        } else {
+            None
+        };
+        let metrics = self
+            .config
+            .emf_namespace
+            .as_ref()
+            .map(|namespace| EmfMetrics::new(namespace, &ctx));
+        let res = base_path
+            .and_then(|base_path| self.process_request(req, &base_path, ctx))
+            .or_else(|e| self.create_error_response(e));
+        if let Some(request_log) = request_log {
+            request_log.log(&res);
+        }
+        if let Some(metrics) = metrics {
+            metrics.emit(&res);
+        }
+        res
+    }
+
+    /// Handles an event from a direct invocation of the function (e.g. by the AWS SDK or a Step Functions task), rather
+    /// than from API Gateway or an Application Load Balancer, by dispatching it to Rocket as a synthetic HTTP request.
+    ///
+    /// The synthetic request is a `POST` to the [direct_invocation_path](crate::RocketHandlerBuilder::direct_invocation_path),
+    /// with a `Content-Type` of `application/json`, a `Host` of `localhost` and the event serialized as JSON as its body.
+    /// It has no query string or other headers, and is treated as a request from an Application Load Balancer, so no
+    /// base path is applied and guards such as [AuthorizerClaims](crate::AuthorizerClaims) forward.
+    ///
+    /// [launch](crate::RocketHandlerBuilder::launch) only handles API Gateway and Application Load Balancer events, so
+    /// direct invocations must be received using your own `lambda_runtime` handler, which can then return the body of
+    /// the response to the caller.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use lambda_runtime::Context;
+    /// use rocket_lamb::RocketExt;
+    /// use serde_json::json;
+    ///
+    /// let handler = rocket::ignite().lambda().into_handler();
+    ///
+    /// let res = handler
+    ///     .handle_direct_invocation(json!({ "orderId": 123 }), Context::default())
+    ///     .unwrap();
+    /// assert_eq!(res.status(), 404);
+    /// ```
+    pub fn handle_direct_invocation(
+        &self,
+        event: Value,
+        ctx: Context,
+    ) -> Result<Response<Body>, RocketLambError> {
+        let synthetic_event = json!({
+            "requestContext": { "elb": { "targetGroupArn": "" } },
+            "path": self.config.direct_invocation_path,
+            "httpMethod": "POST",
+            "headers": {
+                "Content-Type": "application/json",
+                "Host": "localhost",
+            },
+            "queryStringParameters": {},
+            "body": event.to_string(),
+            "isBase64Encoded": false,
+        });
+        let req = lambda_http::request::from_str(&synthetic_event.to_string())
+            .map_err(|e| invalid_request!("{}", e))?;
+        self.try_handle(req, ctx)
+    }
+
+    /// Gets the base path that was set with [base_path](crate::RocketHandlerBuilder::base_path), or otherwise the
+    /// base path of the first request received.
+    fn first_base_path(&self, base_path: &str) -> &str {
+        match &self.config.base_path {
+            Some(base_path) => base_path,
+            None => self.detected_base_path.get_or_init(|| base_path.to_owned()),
+        }
+    }
+
+    fn ensure_client_ready(&self, base_path: &str) {
+        if self.client.get().is_none() {
+            let remount_base_path =
                 if self.config.base_path_behaviour == BasePathBehaviour::RemountAndInclude {
-                    let base_path = req.base_path();
-                    if !base_path.is_empty() {
-                        let routes: Vec<Route> = rocket.routes().cloned().collect();
-                        rocket = rocket.mount(&base_path, routes);
+                    self.first_base_path(base_path)
+                } else {
+                    ""
+                };
+            self.initialize_client(remount_base_path);
+        }
+    }
+
+    fn initialize_client(&self, remount_base_path: &str) {
+        self.client.get_or_init(|| {
+            let mut rocket = self
+                .rocket
+                .lock()
+                .unwrap()
+                .take()
+                .expect("Rocket has previously been used to create a client.");
+            if !remount_base_path.is_empty() {
+                let routes: Vec<Route> = rocket.routes().cloned().collect();
+                rocket = rocket.mount(remount_base_path, routes);
+            }
+            for route in rocket.routes() {
+                debug!("Mounted route: {}", route);
+            }
+            let client = if self.config.tracked_client {
+                Client::new(rocket)
+            } else {
+                Client::untracked(rocket)
+            };
+            client.map_err(|e| {
+                let message = e.to_string();
+                error!("Failed to launch Rocket: {}", message);
+                message
+            })
+        });
+    }
+
+    /// Gets the routes mounted on the `Rocket`, including any routes that were re-mounted at the API Gateway base path
+    /// when the base path behaviour is `RemountAndInclude`. This can help diagnose requests that unexpectedly return
+    /// a 404 response due to a base path mismatch.
+    ///
+    /// Routes are re-mounted when the Rocket `Client` is created, so this returns `None` if the `Client` has not yet been
+    /// created (see [eager_client](crate::RocketHandlerBuilder::eager_client)), or if Rocket failed to launch.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let handler = rocket::ignite().lambda().into_handler();
+    /// assert!(handler.routes().is_none());
+    /// ```
+    pub fn routes(&self) -> Option<Vec<Route>> {
+        match self.client.get() {
+            Some(Ok(client)) => Some(client.rocket().routes().cloned().collect()),
+            _ => None,
+        }
+    }
+
+    /// Discards the Rocket `Client` (if it has been created) and replaces the `Rocket` that it is created from. The new
+    /// `Client` is created in the same way as when the `RocketHandler` was first created, so when the base path
+    /// behaviour is `RemountAndInclude` and no [base_path](crate::RocketHandlerBuilder::base_path) has been set, the
+    /// base path is determined again from the next request received.
+    ///
+    /// This is primarily intended for tests and other advanced scenarios. The handler's configuration is unchanged, and
+    /// any managed state or fairings of the previous `Rocket` are not carried over to the new one.
+    ///
+    /// # Panics
+    ///
+    /// If [eager_client](crate::RocketHandlerBuilder::eager_client) has been called, this panics under the same
+    /// conditions as [into_handler](crate::RocketHandlerBuilder::into_handler).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rocket_lamb::RocketExt;
+    ///
+    /// let mut handler = rocket::ignite().lambda().into_handler();
+    /// handler.reset_client(rocket::ignite());
+    /// ```
+    pub fn reset_client(&mut self, rocket: Rocket) {
+        self.rocket = Mutex::new(Some(rocket));
+        self.client = OnceCell::new();
+        self.detected_base_path = OnceCell::new();
+        if self.config.eager_client {
+            self.initialize_eager_client();
+        }
+    }
+
+    pub(super) fn initialize_eager_client(&self) {
+        let remount_base_path = match self.config.base_path_behaviour {
+            BasePathBehaviour::RemountAndInclude => self.config.base_path.clone().expect(
+                "eager_client cannot be used with BasePathBehaviour::RemountAndInclude unless a base_path is set.",
+            ),
+            _ => String::new(),
+        };
+        self.initialize_client(&remount_base_path);
+    }
+
+    fn client(&self) -> Result<&Client, RocketLambError> {
+        match self.client.get() {
+            Some(Ok(client)) => Ok(client),
+            Some(Err(message)) => Err(RocketLambError::LaunchFailed(message.clone())),
+            None => {
+                panic!("Rocket client wasn't ready. ensure_client_ready should have been called!")
+            }
+        }
+    }
+
+    fn process_request(
+        &self,
+        req: Request,
+        base_path: &str,
+        ctx: Context,
+    ) -> Result<Response<Body>, RocketLambError> {
+        let request_id = match req.request_context() {
+            RequestContext::ApiGateway { request_id, .. } => request_id,
+            RequestContext::Alb { .. } => ctx.aws_request_id.clone(),
+        };
+        let res = self.dispatch_request(req, base_path, ctx)?;
+        self.finalize_response(res, &request_id)
+    }
+
+    fn dispatch_request(
+        &self,
+        req: Request,
+        base_path: &str,
+        ctx: Context,
+    ) -> Result<Response<Body>, RocketLambError> {
+        if let Some(health_check_path) = &self.config.health_check_path {
+            if req.api_path(base_path) == health_check_path.as_str() {
+                return Ok(Response::new(Body::Empty));
+            }
+        }
+        let method = match to_rocket_method(req.method()) {
+            Some(method) => method,
+            None => return method_not_allowed_response(),
+        };
+        if let Some(margin) = self.config.deadline_margin {
+            if remaining_time_ms(&ctx) < margin.as_millis() as i64 {
+                return service_unavailable_response();
+            }
+        }
+        let uri = self.get_path_and_query(&req, base_path);
+        if Origin::parse(&uri).is_err() {
+            return empty_response(400);
+        }
+        if let Some(max_request_size) = self.config.max_request_size {
+            let size = match req.body() {
+                Body::Text(s) => s.len(),
+                Body::Binary(b) => b.len(),
+                Body::Empty => 0,
+            };
+            if size > max_request_size {
+                return empty_response(413);
+            }
+        }
+        let decompressed_body = match self.request_content_encoding(&req) {
+            Some(encoding) => {
+                let max_size = self
+                    .config
+                    .max_request_size
+                    .unwrap_or(DEFAULT_MAX_DECOMPRESSED_REQUEST_SIZE);
+                match decompress_body(&encoding, req.body().as_ref(), max_size) {
+                    Ok(Some(body)) => Some(body),
+                    Ok(None) => return empty_response(413),
+                    Err(e) => {
+                        warn!("Failed to decompress {} request body: {}", encoding, e);
+                        return empty_response(400);
                     }
                 }
-                let client = Client::untracked(rocket).unwrap();
-                self.client = LazyClient::Ready(client);
             }
-            LazyClient::Ready(_) => {}
-            LazyClient::Placeholder => panic!("LazyClient has previously begun initialiation."),
+            None => None,
+        };
+        let local_req = self.create_rocket_request(&req, uri, method, decompressed_body, ctx)?;
+        if let Some(debug_echo_path) = &self.config.debug_echo_path {
+            if req.api_path(base_path) == debug_echo_path.as_str() {
+                return debug_echo_response(&local_req);
+            }
         }
+        let local_res = match panic::catch_unwind(AssertUnwindSafe(|| local_req.dispatch())) {
+            Ok(local_res) => local_res,
+            Err(_) => {
+                error!("Rocket panicked while handling a request.");
+                return empty_response(500);
+            }
+        };
+        if method == Method::Options && local_res.status() == Status::NotFound {
+            if let Some(res) = self.cors_preflight_response(&req) {
+                return res;
+            }
+        }
+        let binary_accepted = self.accepts_binary(&req);
+        self.create_lambda_response(local_res, method, binary_accepted)
     }
 
-    fn client(&self) -> &Client {
-        match &self.client {
-            LazyClient::Ready(client) => client,
-            _ => panic!("Rocket client wasn't ready. ensure_client_ready should have been called!"),
+    /// Applies the response configuration that applies to every response, whether or not it was created by Rocket.
+    fn finalize_response(
+        &self,
+        mut res: Response<Body>,
+        request_id: &str,
+    ) -> Result<Response<Body>, RocketLambError> {
+        if let Some(header_name) = &self.config.request_id_header {
+            let value =
+                HeaderValue::from_str(request_id).map_err(|e| invalid_response!("{}", e))?;
+            res.headers_mut().insert(header_name.clone(), value);
+        }
+        if let Some(on_response) = &self.config.on_response {
+            on_response(&mut res);
         }
+        Ok(
+            match self.config.status_mappings.get(&res.status().as_u16()) {
+                Some(map) => map(res),
+                None => res,
+            },
+        )
     }
 
-    fn process_request(&self, req: Request) -> Result<Response<Body>, RocketLambError> {
-        let local_req = self.create_rocket_request(req)?;
-        let local_res = local_req.dispatch();
-        self.create_lambda_response(local_res)
+    /// Gets the `Content-Encoding` of the request body, if it should be decompressed before it is passed to Rocket.
+    fn request_content_encoding(&self, req: &Request) -> Option<String> {
+        if !self.config.decompress_requests {
+            return None;
+        }
+        req.headers()
+            .get(CONTENT_ENCODING)
+            .and_then(|h| h.to_str().ok())
+            .map(|h| h.trim().to_ascii_lowercase())
+            .filter(|encoding| encoding == "gzip" || encoding == "deflate")
     }
 
-    fn create_rocket_request(&self, req: Request) -> Result<LocalRequest, RocketLambError> {
-        let method = to_rocket_method(req.method())?;
-        let uri = self.get_path_and_query(&req);
-        let mut local_req = self.client().req(method, uri);
+    fn cors_preflight_response(
+        &self,
+        req: &Request,
+    ) -> Option<Result<Response<Body>, RocketLambError>> {
+        let cors = self.config.cors_preflight.as_ref()?;
+        let origin = req.headers().get(ORIGIN)?.to_str().ok()?;
+        req.headers().get(ACCESS_CONTROL_REQUEST_METHOD)?;
+        let allow_origin = if cors.allowed_origins.iter().any(|o| o == "*") {
+            "*"
+        } else if cors.allowed_origins.iter().any(|o| o == origin) {
+            origin
+        } else {
+            return None;
+        };
+
+        let mut builder = Response::builder();
+        builder
+            .status(204)
+            .header(ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin)
+            .header(
+                ACCESS_CONTROL_ALLOW_METHODS,
+                cors.allowed_methods.join(", ").as_str(),
+            );
+        if allow_origin != "*" {
+            builder.header(VARY, "Origin");
+        }
+        if !cors.allowed_headers.is_empty() {
+            builder.header(
+                ACCESS_CONTROL_ALLOW_HEADERS,
+                cors.allowed_headers.join(", ").as_str(),
+            );
+        }
+        if let Some(max_age) = cors.max_age {
+            builder.header(
+                ACCESS_CONTROL_MAX_AGE,
+                max_age.as_secs().to_string().as_str(),
+            );
+        }
+        Some(
+            builder
+                .body(Body::Empty)
+                .map_err(|e| invalid_response!("{}", e)),
+        )
+    }
+
+    fn create_rocket_request(
+        &self,
+        req: &Request,
+        uri: String,
+        method: Method,
+        decompressed_body: Option<Vec<u8>>,
+        ctx: Context,
+    ) -> Result<LocalRequest, RocketLambError> {
+        let mut local_req = self.client()?.req(method, uri);
+        let trace_id = if !ctx.xray_trace_id.is_empty() {
+            Some(ctx.xray_trace_id.clone())
+        } else {
+            env::var("_X_AMZN_TRACE_ID").ok()
+        };
+        let stripped = |name: &str| header_matches(&self.config.stripped_request_headers, name);
         for (name, value) in req.headers() {
+            if (trace_id.is_some() && name == "x-amzn-trace-id")
+                || (self.config.host.is_some() && *name == HOST)
+                || (decompressed_body.is_some()
+                    && (*name == CONTENT_ENCODING || *name == CONTENT_LENGTH))
+                || stripped(name.as_str())
+            {
+                continue;
+            }
             match value.to_str() {
                 Ok(v) => local_req.add_header(Header::new(name.to_string(), v.to_string())),
                 Err(_) => return Err(invalid_request!("invalid value for header '{}'", name)),
             }
         }
-        local_req.set_body(req.into_body());
+        if let Some(host) = &self.config.host {
+            local_req.add_header(Header::new("Host", host.clone()));
+        }
+        if let Some(trace_id) = trace_id {
+            if !stripped("x-amzn-trace-id") {
+                local_req.add_header(Header::new("X-Amzn-Trace-Id", trace_id));
+            }
+        }
+        if !req.headers().contains_key("x-forwarded-proto")
+            && !req.request_context().is_alb()
+            && !stripped("x-forwarded-proto")
+        {
+            // API Gateway only accepts HTTPS requests, so this is `https` unless the scheme has been overridden
+            local_req.add_header(Header::new(
+                "X-Forwarded-Proto",
+                self.config.forwarded_proto.clone(),
+            ));
+        }
+        if let Some(source_ip) = req.source_ip() {
+            if !req.headers().contains_key("x-forwarded-for") && !stripped("x-forwarded-for") {
+                local_req.add_header(Header::new("X-Forwarded-For", source_ip.to_string()));
+            }
+            local_req = local_req.remote(SocketAddr::new(source_ip, 0));
+        }
+        match (req.body(), decompressed_body) {
+            (_, Some(body)) => {
+                local_req.add_header(Header::new("Content-Length", body.len().to_string()));
+                local_req.set_body(body);
+            }
+            (Body::Text(s), None) => local_req.set_body(s),
+            (Body::Binary(b), None) => local_req.set_body(b),
+            (Body::Empty, None) => {}
+        }
+        local_req.inner().local_cache(|| {
+            Some(LambdaRequestState {
+                context: ctx,
+                request_context: req.request_context(),
+                stage_variables: req.stage_variables(),
+                custom_domain: req.is_custom_domain(),
+            })
+        });
+        if let Some(on_request) = &self.config.on_request {
+            on_request(req, &mut local_req);
+        }
         Ok(local_req)
     }
 
     fn create_lambda_response(
         &self,
         mut local_res: LocalResponse,
+        method: Method,
+        binary_accepted: bool,
     ) -> Result<Response<Body>, RocketLambError> {
         let mut builder = Response::builder();
         builder.status(local_res.status().code);
         for h in local_res.headers().iter() {
+            // API Gateway sets these itself to reflect the body it sends, so any values from Rocket may be stale
+            if h.name().eq_ignore_ascii_case("content-length")
+                || h.name().eq_ignore_ascii_case("transfer-encoding")
+                || h.name().eq_ignore_ascii_case(RESPONSE_TYPE_HEADER)
+                || header_matches(&self.config.stripped_response_headers, h.name())
+            {
+                continue;
+            }
             builder.header(&h.name.to_string(), &h.value.to_string());
         }
 
-        let response_type = local_res
-            .headers()
-            .get_one("content-type")
-            .unwrap_or_default()
-            .split(';')
-            .next()
-            .and_then(|ct| self.config.response_types.get(&ct.to_lowercase()))
-            .copied()
+        let status = local_res.status().code;
+        let has_body = local_res.body().is_some() && status != 204 && status != 304;
+        let content_type = match local_res.headers().get_one("content-type") {
+            Some(content_type) => Some(content_type.to_owned()),
+            None if has_body => {
+                let default = self.config.default_content_type.clone();
+                if let Some(default) = &default {
+                    builder.header(CONTENT_TYPE, default.as_str());
+                }
+                default
+            }
+            None => None,
+        };
+        let media_type = content_type.as_deref().map(media_type);
+        let response_type = self
+            .config
+            .status_response_types
+            .iter()
+            .rev()
+            .find(|(statuses, _)| statuses.contains(&status))
+            .map(|(_, response_type)| *response_type)
+            .or_else(|| {
+                local_res
+                    .headers()
+                    .get_one(RESPONSE_TYPE_HEADER)
+                    .and_then(parse_response_type)
+            })
+            .or_else(|| {
+                self.config
+                    .response_type_resolver
+                    .as_ref()
+                    .and_then(|resolver| resolver(&local_res))
+            })
+            .or_else(|| {
+                let disposition = local_res.headers().get_one("content-disposition")?;
+                let disposition_type = disposition.split(';').next().unwrap_or_default();
+                if self.config.binary_attachments
+                    && disposition_type.trim().eq_ignore_ascii_case("attachment")
+                {
+                    Some(ResponseType::Binary)
+                } else {
+                    None
+                }
+            })
+            .or_else(|| {
+                media_type
+                    .as_ref()
+                    .map(|mt| self.config.response_type_for(mt))
+            })
             .unwrap_or(self.config.default_response_type);
         let body = match (local_res.body(), response_type) {
+            // These responses must not have a body, even if Rocket provided one
+            _ if status == 204 || status == 304 => Body::Empty,
+            // Rocket has already removed the body, but the Content-Length should match that of a GET request
+            (Some(b), _) if method == Method::Head => {
+                if let rocket::response::Body::Sized(_, size) = b {
+                    builder.header(CONTENT_LENGTH, &size.to_string());
+                }
+                Body::Empty
+            }
             (Some(b), ResponseType::Auto) => {
+                let bytes = b
+                    .into_bytes()
+                    .ok_or_else(|| invalid_response!("failed to read response body"))?;
+                match media_type {
+                    _ if binary_accepted => Body::Binary(bytes),
+                    Some(ref mt) if !is_text_media_type(mt) => Body::Binary(bytes),
+                    _ => match String::from_utf8(bytes) {
+                        Ok(s) => Body::Text(s),
+                        Err(e) => Body::Binary(e.into_bytes()),
+                    },
+                }
+            }
+            (Some(b), ResponseType::Text) => {
                 let bytes = b
                     .into_bytes()
                     .ok_or_else(|| invalid_response!("failed to read response body"))?;
                 match String::from_utf8(bytes) {
                     Ok(s) => Body::Text(s),
-                    Err(e) => Body::Binary(e.into_bytes()),
+                    Err(e) if self.config.text_fallback_to_binary => Body::Binary(e.into_bytes()),
+                    Err(_) => {
+                        return Err(invalid_response!("failed to read response body as UTF-8"))
+                    }
                 }
             }
-            (Some(b), ResponseType::Text) => Body::Text(
-                b.into_string()
-                    .ok_or_else(|| invalid_response!("failed to read response body as UTF-8"))?,
-            ),
             (Some(b), ResponseType::Binary) => Body::Binary(
                 b.into_bytes()
                     .ok_or_else(|| invalid_response!("failed to read response body"))?,
@@ -120,25 +671,109 @@ impl RocketHandler {
             (None, _) => Body::Empty,
         };
 
+        let body = match self.config.truncate_response_size {
+            Some(max_size) if encoded_len(&body) > max_size => {
+                warn!(
+                    "Truncating response body of {} bytes to the maximum size of {} bytes",
+                    encoded_len(&body),
+                    max_size
+                );
+                builder.header(TRUNCATED_HEADER, "true");
+                truncate_body(body, max_size)
+            }
+            _ => body,
+        };
+
+        if let Some(max_size) = self.config.max_response_size {
+            let size = encoded_len(&body);
+            if size > max_size {
+                return Err(invalid_response!(
+                    "response body of {} bytes exceeds the maximum size of {} bytes",
+                    size,
+                    max_size
+                ));
+            }
+        }
+
         builder.body(body).map_err(|e| invalid_response!("{}", e))
     }
 
-    fn get_path_and_query(&self, req: &Request) -> String {
+    fn accepts_binary(&self, req: &Request) -> bool {
+        if self.config.binary_media_types.is_empty() {
+            return false;
+        }
+        // API Gateway only considers the first media type in the `Accept` header
+        let accepted = match req
+            .headers()
+            .get(ACCEPT)
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| split_unquoted(h, ',').into_iter().next())
+        {
+            Some(accepted) => media_type(accepted),
+            None => return false,
+        };
+        self.config
+            .binary_media_types
+            .iter()
+            .any(|binary| media_type_matches(binary, &accepted))
+    }
+
+    fn create_error_response(
+        &self,
+        error: RocketLambError,
+    ) -> Result<Response<Body>, RocketLambError> {
+        match self.config.error_response_status {
+            Some(status) => Response::builder()
+                .status(status)
+                .header(CONTENT_TYPE, "text/plain; charset=utf-8")
+                .body(Body::Text(error.to_string()))
+                .map_err(|e| invalid_response!("{}", e)),
+            None => Err(error),
+        }
+    }
+
+    fn get_path_and_query(&self, req: &Request, base_path: &str) -> String {
         let mut uri = match self.config.base_path_behaviour {
-            BasePathBehaviour::Include | BasePathBehaviour::RemountAndInclude => req.full_path(),
-            BasePathBehaviour::Exclude => req.api_path().to_owned(),
+            BasePathBehaviour::Include | BasePathBehaviour::RemountAndInclude => {
+                req.full_path(base_path)
+            }
+            BasePathBehaviour::IncludeFromFirstRequest => {
+                let mut path = self.first_base_path(base_path).to_owned();
+                path.push_str(req.api_path(base_path));
+                path
+            }
+            BasePathBehaviour::ExcludeStage => req.uri().path().to_owned(),
+            BasePathBehaviour::Exclude => req.api_path(base_path).to_owned(),
         };
+        match self.config.trailing_slash {
+            TrailingSlash::Keep => {}
+            TrailingSlash::Strip => {
+                let len = uri.trim_end_matches('/').len().max(1);
+                uri.truncate(len);
+            }
+            TrailingSlash::Add => {
+                if !uri.ends_with('/') {
+                    uri.push('/');
+                }
+            }
+        }
+        // `lambda_http` uses `multiValueQueryStringParameters` when it is present, so repeated parameters are not lost.
         let query = req.query_string_parameters();
+        // The original order of the parameters is not available, so sort them to give a consistent order.
+        let mut keys: Vec<&str> = query.iter().map(|(key, _)| key).collect();
+        keys.sort();
 
         let mut separator = '?';
-        for (key, _) in query.iter() {
+        for key in keys {
             for value in query.get_all(key).unwrap() {
-                uri.push_str(&format!(
-                    "{}{}={}",
-                    separator,
-                    Uri::percent_encode(key),
-                    Uri::percent_encode(value)
-                ));
+                uri.push(separator);
+                uri.push_str(&Uri::percent_encode(key));
+                // API Gateway provides valueless parameters (e.g. `?flag`) as having an empty value, so they cannot be
+                // distinguished from `?flag=`. Rocket parses both forms in the same way.
+                if !value.is_empty() {
+                    uri.push('=');
+                    uri.push_str(&Uri::percent_encode(value));
+                }
                 separator = '&';
             }
         }
@@ -146,10 +781,10 @@ impl RocketHandler {
     }
 }
 
-fn to_rocket_method(method: &http::Method) -> Result<rocket::http::Method, RocketLambError> {
+fn to_rocket_method(method: &http::Method) -> Option<Method> {
     use http::Method as H;
     use rocket::http::Method::*;
-    Ok(match *method {
+    Some(match *method {
         H::GET => Get,
         H::PUT => Put,
         H::POST => Post,
@@ -159,6 +794,183 @@ fn to_rocket_method(method: &http::Method) -> Result<rocket::http::Method, Rocke
         H::TRACE => Trace,
         H::CONNECT => Connect,
         H::PATCH => Patch,
-        _ => return Err(invalid_request!("unknown method '{}'", method)),
+        _ => return None,
     })
 }
+
+/// Decompresses a request body, returning `None` if the decompressed body would be larger than `max_size`. The output
+/// is limited while it is being read, so a small compressed body cannot expand to an arbitrary size.
+fn decompress_body(encoding: &str, body: &[u8], max_size: usize) -> io::Result<Option<Vec<u8>>> {
+    let limit = (max_size as u64).saturating_add(1);
+    let mut decompressed = Vec::new();
+    if encoding == "gzip" {
+        GzDecoder::new(body)
+            .take(limit)
+            .read_to_end(&mut decompressed)?;
+    } else {
+        // The `deflate` content coding is the zlib format, rather than raw deflate
+        ZlibDecoder::new(body)
+            .take(limit)
+            .read_to_end(&mut decompressed)?;
+    }
+    if decompressed.len() > max_size {
+        Ok(None)
+    } else {
+        Ok(Some(decompressed))
+    }
+}
+
+fn header_matches(patterns: &[String], name: &str) -> bool {
+    let name = name.to_lowercase();
+    patterns.iter().any(|pattern| {
+        if pattern.ends_with('*') {
+            name.starts_with(&pattern[..pattern.len() - 1])
+        } else {
+            name == *pattern
+        }
+    })
+}
+
+fn parse_response_type(value: &str) -> Option<ResponseType> {
+    match value.trim().to_lowercase().as_str() {
+        "auto" => Some(ResponseType::Auto),
+        "text" => Some(ResponseType::Text),
+        "binary" => Some(ResponseType::Binary),
+        _ => None,
+    }
+}
+
+fn method_not_allowed_response() -> Result<Response<Body>, RocketLambError> {
+    Response::builder()
+        .status(405)
+        .header(
+            ALLOW,
+            "GET, PUT, POST, DELETE, OPTIONS, HEAD, TRACE, CONNECT, PATCH",
+        )
+        .body(Body::Empty)
+        .map_err(|e| invalid_response!("{}", e))
+}
+
+fn empty_response(status: u16) -> Result<Response<Body>, RocketLambError> {
+    Response::builder()
+        .status(status)
+        .body(Body::Empty)
+        .map_err(|e| invalid_response!("{}", e))
+}
+
+fn debug_echo_response(local_req: &LocalRequest) -> Result<Response<Body>, RocketLambError> {
+    let req = local_req.inner();
+    let headers: Vec<Value> = req
+        .headers()
+        .iter()
+        .map(|h| json!({ "name": h.name(), "value": h.value() }))
+        .collect();
+    let body = json!({
+        "method": req.method().as_str(),
+        "path": req.uri().path(),
+        "query": req.uri().query(),
+        "headers": headers,
+    });
+    Response::builder()
+        .status(200)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::Text(body.to_string()))
+        .map_err(|e| invalid_response!("{}", e))
+}
+
+fn service_unavailable_response() -> Result<Response<Body>, RocketLambError> {
+    Response::builder()
+        .status(503)
+        .header(RETRY_AFTER, "1")
+        .body(Body::Empty)
+        .map_err(|e| invalid_response!("{}", e))
+}
+
+fn remaining_time_ms(ctx: &Context) -> i64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64;
+    ctx.deadline - now
+}
+
+fn encoded_len(body: &Body) -> usize {
+    match body {
+        Body::Empty => 0,
+        Body::Text(s) => s.len(),
+        Body::Binary(b) => (b.len() + 2) / 3 * 4,
+    }
+}
+
+/// Truncates a body so that its encoded length does not exceed `max_size`. Text is truncated at a character boundary.
+fn truncate_body(body: Body, max_size: usize) -> Body {
+    match body {
+        Body::Empty => Body::Empty,
+        Body::Text(mut s) => {
+            let mut len = max_size;
+            while !s.is_char_boundary(len) {
+                len -= 1;
+            }
+            s.truncate(len);
+            Body::Text(s)
+        }
+        Body::Binary(mut b) => {
+            b.truncate(max_size / 4 * 3);
+            Body::Binary(b)
+        }
+    }
+}
+
+/// Gets the lowercase media type (e.g. `text/html`) of a Content-Type or Accept value. A `;` cannot appear in the type
+/// or subtype, so everything before the first `;` is the media type, even if a quoted parameter contains a `;`.
+fn media_type(value: &str) -> String {
+    value
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase()
+}
+
+/// Splits a header value on a separator, ignoring separators within quoted strings.
+fn split_unquoted(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (i, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            c if c == separator && !in_quotes => {
+                parts.push(&value[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
+
+fn media_type_matches(pattern: &str, media_type: &str) -> bool {
+    if pattern == "*/*" || pattern == media_type {
+        return true;
+    }
+    match (pattern.split('/').next(), media_type.split('/').next()) {
+        (Some(pattern_type), Some(top_level_type)) => {
+            pattern.ends_with("/*") && pattern_type == top_level_type
+        }
+        _ => false,
+    }
+}
+
+fn is_text_media_type(media_type: &str) -> bool {
+    media_type.starts_with("text/")
+        || media_type == "application/json"
+        || media_type == "application/xml"
+        || media_type == "application/javascript"
+        || media_type.ends_with("+json")
+        || media_type.ends_with("+xml")
+}
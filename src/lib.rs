@@ -5,6 +5,14 @@ The function takes a request from an AWS API Gateway Proxy and converts it into
 
 This *should* also work with requests from an AWS Application Load Balancer, but this has not been tested.
 
+API Gateway HTTP APIs are supported when their integration is configured to use payload format version 1.0. Payload format version 2.0 cannot currently be deserialized by `lambda_http`.
+
+API Gateway REST APIs and Application Load Balancers only provide a request's query string parameters as a map, without the raw query string, so their original order is not preserved. The parameters are passed to Rocket sorted by name, with repeated parameters kept in their original order. API Gateway also provides parameters without a value (`?key`) and with an empty value (`?key=`) in the same way, so both are passed to Rocket as `?key`. Rocket parses these forms identically, so this does not affect query guards such as `Option<String>`.
+
+The request path is passed to Rocket as it is received from API Gateway, without being decoded, so percent-encoded characters in a path segment (e.g. an encoded slash `%2F`) are decoded by Rocket when the segment is matched by a route. When determining the base path, the path and the request's path parameters are both decoded before they are compared, as API Gateway may provide path parameters in decoded form.
+
+Rocket only runs launch fairings (`on_launch`) when it starts its own HTTP server, so they are not run when Rocket is used as a Lambda function. Attach fairings (`on_attach`) are run as usual when they are attached, so they should be used for any setup that must happen before requests are handled, e.g. creating a database pool.
+
 ## Usage
 
 ```rust,no_run
@@ -30,20 +38,28 @@ fn main() {
 #![allow(clippy::large_enum_variant)]
 
 use rocket::Rocket;
-
-#[macro_use]
-extern crate failure;
+use std::env;
 
 #[macro_use]
 mod error;
 
 mod builder;
 mod config;
+mod embedded;
+mod guards;
 mod handler;
+mod logging;
+mod metrics;
 mod request_ext;
 
 pub use builder::*;
 pub use config::*;
+pub use embedded::EmbeddedFiles;
+pub use error::RocketLambError;
+pub use guards::{
+    AuthorizerClaims, IsCustomDomain, LambdaContext, RawApiGatewayContext, RequestContextJson,
+    ResourcePath, StageVariables, TraceId,
+};
 pub use handler::*;
 
 /// Extensions for `rocket::Rocket` to make it easier to create Lambda handlers.
@@ -65,3 +81,25 @@ impl RocketExt for Rocket {
         RocketHandlerBuilder::new(self)
     }
 }
+
+/// Sets the `ROCKET_ENV` environment variable to `production` when running in AWS Lambda, unless it has already been set.
+///
+/// This prevents development-only behaviour (e.g. verbose logging) when the function is deployed to Lambda, without
+/// affecting the environment used when running locally. Running in Lambda is detected by the presence of the
+/// `AWS_LAMBDA_FUNCTION_NAME` environment variable.
+///
+/// Rocket determines its environment when it is created, so this must be called before `rocket::ignite()`.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use rocket_lamb::RocketExt;
+///
+/// rocket_lamb::use_lambda_environment();
+/// rocket::ignite().lambda().launch();
+/// ```
+pub fn use_lambda_environment() {
+    if env::var_os("AWS_LAMBDA_FUNCTION_NAME").is_some() && env::var_os("ROCKET_ENV").is_none() {
+        env::set_var("ROCKET_ENV", "production");
+    }
+}
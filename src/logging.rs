@@ -0,0 +1,52 @@
+use crate::error::RocketLambError;
+use lambda_http::{Body, Request, Response};
+use lambda_runtime::Context;
+use log::{error, info};
+use serde_json::json;
+use std::time::Instant;
+
+/// Details of a request, which are logged as a single JSON line once the request has been handled.
+pub(crate) struct RequestLog {
+    request_id: String,
+    method: String,
+    path: String,
+    start: Instant,
+}
+
+impl RequestLog {
+    pub(crate) fn new(req: &Request, path: String, ctx: &Context) -> RequestLog {
+        RequestLog {
+            request_id: ctx.aws_request_id.clone(),
+            method: req.method().to_string(),
+            path,
+            start: Instant::now(),
+        }
+    }
+
+    pub(crate) fn log(self, res: &Result<Response<Body>, RocketLambError>) {
+        let duration_ms = self.start.elapsed().as_secs_f64() * 1000.0;
+        match res {
+            Ok(res) => info!(
+                "{}",
+                json!({
+                    "requestId": self.request_id,
+                    "method": self.method,
+                    "path": self.path,
+                    "status": res.status().as_u16(),
+                    "bodySize": res.body().as_ref().len(),
+                    "durationMs": duration_ms,
+                })
+            ),
+            Err(e) => error!(
+                "{}",
+                json!({
+                    "requestId": self.request_id,
+                    "method": self.method,
+                    "path": self.path,
+                    "error": e.to_string(),
+                    "durationMs": duration_ms,
+                })
+            ),
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use crate::error::RocketLambError;
+use lambda_http::{Body, Response};
+use lambda_runtime::Context;
+use serde_json::{json, Value};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Metrics of a request, which are written to stdout in CloudWatch Embedded Metric Format once the request has been
+/// handled.
+pub(crate) struct EmfMetrics {
+    namespace: String,
+    function_name: String,
+    start: Instant,
+}
+
+impl EmfMetrics {
+    pub(crate) fn new(namespace: &str, ctx: &Context) -> EmfMetrics {
+        EmfMetrics {
+            namespace: namespace.to_owned(),
+            function_name: ctx.function_name.clone(),
+            start: Instant::now(),
+        }
+    }
+
+    pub(crate) fn emit(self, res: &Result<Response<Body>, RocketLambError>) {
+        println!("{}", self.to_json(res));
+    }
+
+    fn to_json(&self, res: &Result<Response<Body>, RocketLambError>) -> Value {
+        let latency_ms = self.start.elapsed().as_secs_f64() * 1000.0;
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        // A failed request causes API Gateway to return a 502 response, so it is counted as a server error
+        let status_class = match res {
+            Ok(res) => res.status().as_u16() / 100,
+            Err(_) => 5,
+        };
+        let count = |class: u16| if status_class == class { 1 } else { 0 };
+        json!({
+            "_aws": {
+                "Timestamp": timestamp_ms,
+                "CloudWatchMetrics": [{
+                    "Namespace": self.namespace,
+                    "Dimensions": [["FunctionName"]],
+                    "Metrics": [
+                        { "Name": "2xx", "Unit": "Count" },
+                        { "Name": "3xx", "Unit": "Count" },
+                        { "Name": "4xx", "Unit": "Count" },
+                        { "Name": "5xx", "Unit": "Count" },
+                        { "Name": "Latency", "Unit": "Milliseconds" },
+                    ],
+                }],
+            },
+            "FunctionName": self.function_name,
+            "2xx": count(2),
+            "3xx": count(3),
+            "4xx": count(4),
+            "5xx": count(5),
+            "Latency": latency_ms,
+        })
+    }
+}
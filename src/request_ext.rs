@@ -1,72 +1,149 @@
+use crate::error::RocketLambError;
 use http::header::HOST;
 use lambda_http::request::RequestContext;
 use lambda_http::{Request, RequestExt as _};
+use rocket::http::uri::Uri;
+use std::net::IpAddr;
 
 pub(crate) trait RequestExt {
-    fn full_path(&self) -> String;
+    fn full_path(&self, base_path: &str) -> String;
 
-    fn base_path(&self) -> String;
+    fn base_path(&self, segments: Option<usize>) -> Result<String, RocketLambError>;
 
-    fn api_path(&self) -> &str;
+    fn api_path(&self, base_path: &str) -> &str;
+
+    fn source_ip(&self) -> Option<IpAddr>;
+
+    fn is_custom_domain(&self) -> bool;
 }
 
 impl RequestExt for Request {
-    fn full_path(&self) -> String {
+    fn full_path(&self, base_path: &str) -> String {
         if self.request_context().is_alb() || !is_default_api_gateway_url(self) {
             self.uri().path().to_owned()
         } else {
-            let mut path = self.base_path();
+            let mut path = base_path.to_owned();
             path.push_str(self.uri().path());
             path
         }
     }
 
-    fn base_path(&self) -> String {
-        match self.request_context() {
-            RequestContext::ApiGateway {
+    fn base_path(&self, segments: Option<usize>) -> Result<String, RocketLambError> {
+        // `request_context()` panics if the request has no request context, so this is checked first
+        match self.extensions().get::<RequestContext>() {
+            Some(RequestContext::ApiGateway {
                 stage,
                 resource_path,
                 ..
-            } => {
+            }) => {
                 if is_default_api_gateway_url(self) {
-                    format!("/{}", stage)
-                } else {
-                    let resource_path = populate_resource_path(self, resource_path);
+                    Ok(format!("/{}", stage))
+                } else if let Some(segments) = segments {
                     let full_path = self.uri().path();
-                    let resource_path_index =
-                        full_path.rfind(&resource_path).unwrap_or_else(|| {
-                            panic!(
-                                "Could not find segment '{}' in path '{}'.",
-                                resource_path, full_path
+                    leading_segments(full_path, segments)
+                        .map(str::to_owned)
+                        .ok_or_else(|| {
+                            invalid_request!(
+                                "could not find {} base path segments in path '{}'",
+                                segments,
+                                full_path
                             )
-                        });
-                    full_path[..resource_path_index].to_owned()
+                        })
+                } else {
+                    let resource_path = populate_resource_path(self, resource_path.clone())?;
+                    // The resource path is always at the end of the full path, so it is matched as a suffix rather
+                    // than searched for, as it may also appear within a multi-segment base path. Path parameters
+                    // may be decoded even when the path is not, so both are decoded before being compared.
+                    let full_path = self.uri().path();
+                    let decoded_full_path = Uri::percent_decode_lossy(full_path.as_bytes());
+                    let decoded_resource_path = Uri::percent_decode_lossy(resource_path.as_bytes());
+                    let trimmed_full_path = decoded_full_path.trim_end_matches('/');
+                    let trimmed_resource_path = decoded_resource_path.trim_end_matches('/');
+                    let base_path = if trimmed_full_path.ends_with(trimmed_resource_path) {
+                        let base_path_len = trimmed_full_path.len() - trimmed_resource_path.len();
+                        Some(&trimmed_full_path[..base_path_len])
+                    } else {
+                        None
+                    };
+                    match base_path {
+                        Some(base_path) if full_path.starts_with(base_path) => {
+                            Ok(base_path.to_owned())
+                        }
+                        _ => Err(invalid_request!(
+                            "could not find segment '{}' in path '{}'",
+                            resource_path,
+                            full_path
+                        )),
+                    }
                 }
             }
-            RequestContext::Alb { .. } => String::new(),
+            Some(RequestContext::Alb { .. }) => Ok(String::new()),
+            None => Err(RocketLambError::UnsupportedEvent(
+                "request has no API Gateway or Application Load Balancer request context"
+                    .to_owned(),
+            )),
         }
     }
 
-    fn api_path(&self) -> &str {
+    fn api_path(&self, base_path: &str) -> &str {
         if self.request_context().is_alb() || is_default_api_gateway_url(self) {
             self.uri().path()
         } else {
-            &self.uri().path()[self.base_path().len()..]
+            match &self.uri().path()[base_path.len()..] {
+                "" => "/",
+                api_path => api_path,
+            }
+        }
+    }
+
+    fn source_ip(&self) -> Option<IpAddr> {
+        match self.request_context() {
+            RequestContext::ApiGateway { identity, .. } => identity.source_ip.parse().ok(),
+            RequestContext::Alb { .. } => self
+                .headers()
+                .get("x-forwarded-for")
+                .and_then(|h| h.to_str().ok())
+                // The load balancer appends the address of the client it received the request from, so any earlier
+                // entries were sent by the client and cannot be trusted
+                .and_then(|h| h.rsplit(',').next())
+                .and_then(|ip| ip.trim().parse().ok()),
         }
     }
+
+    fn is_custom_domain(&self) -> bool {
+        !self.request_context().is_alb() && !is_default_api_gateway_url(self)
+    }
 }
 
 fn is_default_api_gateway_url(req: &Request) -> bool {
     req.headers()
         .get(HOST)
         .and_then(|h| h.to_str().ok())
-        .map(|h| h.ends_with(".amazonaws.com") && h.contains(".execute-api."))
+        .map(|h| {
+            let host = h.to_lowercase();
+            // The China regions use a different top-level domain to all other AWS partitions
+            (host.ends_with(".amazonaws.com") || host.ends_with(".amazonaws.com.cn"))
+                && host.contains(".execute-api.")
+        })
         .unwrap_or(false)
 }
 
-fn populate_resource_path(req: &Request, resource_path: String) -> String {
+/// Gets the first `count` segments of `path`, including their leading slashes, if `path` has at least `count` segments.
+fn leading_segments(path: &str, count: usize) -> Option<&str> {
+    let mut end = 0;
+    for _ in 0..count {
+        let rest = &path[end..];
+        if !rest.starts_with('/') || rest.len() == 1 {
+            return None;
+        }
+        end += 1 + rest[1..].find('/').unwrap_or(rest.len() - 1);
+    }
+    Some(&path[..end])
+}
+
+fn populate_resource_path(req: &Request, resource_path: String) -> Result<String, RocketLambError> {
     let path_parameters = req.path_parameters();
-    resource_path
+    Ok(resource_path
         .split('/')
         .map(|segment| {
             if segment.starts_with('{') {
@@ -74,11 +151,11 @@ fn populate_resource_path(req: &Request, resource_path: String) -> String {
                 let param = &segment[1..segment.len() - end];
                 path_parameters
                     .get(param)
-                    .unwrap_or_else(|| panic!("Could not find path parameter '{}'.", param))
+                    .ok_or_else(|| invalid_request!("could not find path parameter '{}'", param))
             } else {
-                segment
+                Ok(segment)
             }
         })
-        .collect::<Vec<&str>>()
-        .join("/")
+        .collect::<Result<Vec<&str>, RocketLambError>>()?
+        .join("/"))
 }
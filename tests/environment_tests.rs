@@ -0,0 +1,15 @@
+use rocket::config::Environment;
+use std::env;
+
+// This is the only test in this file, as it modifies the environment of the test process.
+#[test]
+fn lambda_environment() {
+    env::remove_var("ROCKET_ENV");
+    env::set_var("AWS_LAMBDA_FUNCTION_NAME", "test-function");
+
+    rocket_lamb::use_lambda_environment();
+    let rocket = rocket::ignite();
+
+    assert_eq!(env::var("ROCKET_ENV"), Ok("production".to_owned()));
+    assert_eq!(rocket.config().environment, Environment::Production);
+}
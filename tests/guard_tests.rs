@@ -0,0 +1,237 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use]
+extern crate rocket;
+
+use lambda_http::request::RequestContext;
+use lambda_http::{Body, Handler, Request};
+use lambda_runtime::Context;
+use rocket_lamb::{
+    AuthorizerClaims, IsCustomDomain, LambdaContext, RawApiGatewayContext, RequestContextJson,
+    ResourcePath, RocketExt, StageVariables, TraceId,
+};
+use std::error::Error;
+use std::fs::File;
+
+#[get("/context")]
+fn context(context: LambdaContext) -> String {
+    context.aws_request_id.clone()
+}
+
+#[get("/claims")]
+fn claims(claims: Option<AuthorizerClaims>) -> String {
+    match claims {
+        Some(claims) => claims["sub"].as_str().unwrap_or_default().to_owned(),
+        None => "no claims".to_owned(),
+    }
+}
+
+#[get("/stage-variable/<name>")]
+fn stage_variable(name: String, stage_variables: StageVariables) -> Option<String> {
+    stage_variables.get(&name).map(str::to_owned)
+}
+
+#[get("/raw-context")]
+fn raw_context(context: RawApiGatewayContext) -> String {
+    match &*context {
+        RequestContext::ApiGateway { request_id, .. } => request_id.clone(),
+        RequestContext::Alb { elb } => elb.target_group_arn.clone(),
+    }
+}
+
+#[get("/context-json")]
+fn context_json(context: RequestContextJson) -> String {
+    format!(
+        "{} {}",
+        context["requestId"].as_str().unwrap_or_default(),
+        context["identity"]["sourceIp"].as_str().unwrap_or_default()
+    )
+}
+
+#[get("/users/<id>")]
+fn resource_path(id: u32, resource_path: ResourcePath) -> String {
+    format!("{} {}", id, resource_path.into_inner())
+}
+
+#[get("/trace-id")]
+fn trace_id(trace_id: TraceId) -> String {
+    trace_id.into_inner()
+}
+
+#[get("/custom-domain")]
+fn custom_domain(custom_domain: IsCustomDomain) -> String {
+    custom_domain.0.to_string()
+}
+
+fn make_rocket() -> rocket::Rocket {
+    rocket::ignite().mount(
+        "/",
+        routes![
+            context,
+            claims,
+            stage_variable,
+            raw_context,
+            context_json,
+            resource_path,
+            trace_id,
+            custom_domain
+        ],
+    )
+}
+
+fn get_request(json_file: &str) -> Result<Request, Box<dyn Error>> {
+    let file = File::open(format!("tests/requests/{}.json", json_file))?;
+    Ok(lambda_http::request::from_reader(file)?)
+}
+
+#[test]
+fn lambda_context() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("context")?;
+    let ctx = Context {
+        aws_request_id: "52fdfc07-2182-154f-163f-5f0f9a621d72".to_owned(),
+        ..Context::default()
+    };
+    let res = handler.run(req, ctx)?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(
+        *res.body(),
+        Body::Text("52fdfc07-2182-154f-163f-5f0f9a621d72".to_string())
+    );
+    Ok(())
+}
+
+macro_rules! claims_test_case {
+    ($name:ident, $file:expr, $body:expr) => {
+        #[test]
+        fn $name() -> Result<(), Box<dyn Error>> {
+            let mut handler = make_rocket().lambda().into_handler();
+
+            let req = get_request($file)?;
+            let res = handler.run(req, Context::default())?;
+
+            assert_eq!(res.status(), 200);
+            assert_eq!(*res.body(), Body::Text($body.to_string()));
+            Ok(())
+        }
+    };
+}
+
+claims_test_case!(cognito_authorizer_claims, "claims_cognito", "user-sub");
+claims_test_case!(lambda_authorizer_claims, "claims_lambda", "user-sub");
+claims_test_case!(no_authorizer_claims, "claims_none", "no claims");
+
+#[test]
+fn stage_variables() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("stage_variables")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("blue".to_string()));
+    Ok(())
+}
+
+#[test]
+fn raw_api_gateway_context() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("raw_context")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("test-invoke-request".to_string()));
+    Ok(())
+}
+
+#[test]
+fn request_context_json() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("context_json")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(
+        *res.body(),
+        Body::Text("test-invoke-request 1.2.3.4".to_string())
+    );
+    Ok(())
+}
+
+#[test]
+fn resource_path_template() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("resource_path")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("123 /users/{id}".to_string()));
+    Ok(())
+}
+
+#[test]
+fn trace_id_from_header() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("trace_id")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(
+        *res.body(),
+        Body::Text("Root=1-5e1b4151-5ac6c58f5b5dbd6a2b4f8d5c".to_string())
+    );
+    Ok(())
+}
+
+#[test]
+fn trace_id_from_context() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("trace_id")?;
+    let ctx = Context {
+        xray_trace_id: "Root=1-5e1b4151-5ac6c58f5b5dbd6a2b4f8d5c;Parent=53995c3f42cd8ad8;Sampled=1"
+            .to_owned(),
+        ..Context::default()
+    };
+    let res = handler.run(req, ctx)?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(
+        *res.body(),
+        Body::Text(
+            "Root=1-5e1b4151-5ac6c58f5b5dbd6a2b4f8d5c;Parent=53995c3f42cd8ad8;Sampled=1"
+                .to_string()
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn is_custom_domain() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("custom_domain")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("true".to_string()));
+    Ok(())
+}
+
+#[test]
+fn is_not_custom_domain() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("custom_domain_default_url")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("false".to_string()));
+    Ok(())
+}
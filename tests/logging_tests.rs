@@ -0,0 +1,77 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use]
+extern crate rocket;
+
+use lambda_http::Handler;
+use lambda_runtime::Context;
+use log::{Level, Log, Metadata, Record};
+use rocket_lamb::RocketExt;
+use serde_json::{json, Value};
+use std::error::Error;
+use std::fs::File;
+use std::sync::Mutex;
+
+struct TestLogger {
+    lines: Mutex<Vec<String>>,
+}
+
+impl Log for TestLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target().starts_with("rocket_lamb")
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) && record.level() == Level::Info {
+            self.lines.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+#[get("/path")]
+fn path() -> &'static str {
+    "path"
+}
+
+// This is the only test in this file, as it sets the global logger of the test process.
+#[test]
+fn request_logging() -> Result<(), Box<dyn Error>> {
+    let logger: &'static TestLogger = Box::leak(Box::new(TestLogger {
+        lines: Mutex::new(Vec::new()),
+    }));
+    log::set_logger(logger)?;
+    log::set_max_level(log::LevelFilter::Info);
+
+    let mut handler = rocket::ignite()
+        .mount("/", routes![path])
+        .lambda()
+        .request_logging()
+        .into_handler();
+
+    let file = File::open("tests/requests/path_custom_domain.json")?;
+    let req = lambda_http::request::from_reader(file)?;
+    let ctx = Context {
+        aws_request_id: "52fdfc07-2182-154f-163f-5f0f9a621d72".to_owned(),
+        ..Context::default()
+    };
+    handler.run(req, ctx)?;
+
+    let lines = logger.lines.lock().unwrap();
+    assert_eq!(lines.len(), 1, "Exactly 1 line should be logged");
+    let mut log: Value = serde_json::from_str(&lines[0])?;
+    assert!(log["durationMs"].is_number());
+    log.as_object_mut().unwrap().remove("durationMs");
+    assert_eq!(
+        log,
+        json!({
+            "requestId": "52fdfc07-2182-154f-163f-5f0f9a621d72",
+            "method": "GET",
+            "path": "/path/",
+            "status": 200,
+            "bodySize": 4,
+        })
+    );
+    Ok(())
+}
@@ -0,0 +1,89 @@
+#![feature(proc_macro_hygiene, decl_macro)]
+
+#[macro_use]
+extern crate rocket;
+
+use lambda_http::Handler;
+use lambda_runtime::Context;
+use rocket_lamb::RocketExt;
+use serde_json::{json, Value};
+use std::env;
+use std::error::Error;
+use std::fs::File;
+use std::process::Command;
+
+const CHILD_ENV_VAR: &str = "ROCKET_LAMB_METRICS_TEST_CHILD";
+
+#[get("/path")]
+fn path() -> &'static str {
+    "path"
+}
+
+// Metrics are written to stdout, which cannot be captured within the test process, so this test runs itself again in
+// a child process with output capturing disabled, and reads the child's stdout.
+#[test]
+fn emf_metrics() -> Result<(), Box<dyn Error>> {
+    if env::var_os(CHILD_ENV_VAR).is_some() {
+        let mut handler = rocket::ignite()
+            .mount("/", routes![path])
+            .lambda()
+            .emf_metrics("TestNamespace")
+            .into_handler();
+
+        for json_file in &["path_custom_domain", "not_found"] {
+            let file = File::open(format!("tests/requests/{}.json", json_file))?;
+            let req = lambda_http::request::from_reader(file)?;
+            let ctx = Context {
+                function_name: "test-function".to_owned(),
+                ..Context::default()
+            };
+            handler.run(req, ctx)?;
+        }
+        return Ok(());
+    }
+
+    let output = Command::new(env::current_exe()?)
+        .args(&["emf_metrics", "--exact", "--nocapture", "--test-threads=1"])
+        .env(CHILD_ENV_VAR, "1")
+        .output()?;
+    assert!(output.status.success(), "Child test process failed");
+
+    let stdout = String::from_utf8(output.stdout)?;
+    let metrics = stdout
+        .lines()
+        .filter(|line| line.starts_with("{\"_aws\""))
+        .map(serde_json::from_str)
+        .collect::<Result<Vec<Value>, _>>()?;
+    assert_eq!(metrics.len(), 2, "Exactly 2 metrics should be written");
+
+    for (mut metric, (count_2xx, count_4xx)) in metrics.into_iter().zip(vec![(1, 0), (0, 1)]) {
+        assert!(metric["_aws"]["Timestamp"].is_number());
+        assert!(metric["Latency"].is_number());
+        metric["_aws"].as_object_mut().unwrap().remove("Timestamp");
+        metric.as_object_mut().unwrap().remove("Latency");
+        assert_eq!(
+            metric,
+            json!({
+                "_aws": {
+                    "CloudWatchMetrics": [{
+                        "Namespace": "TestNamespace",
+                        "Dimensions": [["FunctionName"]],
+                        "Metrics": [
+                            { "Name": "2xx", "Unit": "Count" },
+                            { "Name": "3xx", "Unit": "Count" },
+                            { "Name": "4xx", "Unit": "Count" },
+                            { "Name": "5xx", "Unit": "Count" },
+                            { "Name": "Latency", "Unit": "Milliseconds" },
+                        ],
+                    }],
+                },
+                "FunctionName": "test-function",
+                "2xx": count_2xx,
+                "3xx": 0,
+                "4xx": count_4xx,
+                "5xx": 0,
+            })
+        );
+    }
+    Ok(())
+}
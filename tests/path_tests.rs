@@ -6,10 +6,15 @@ extern crate rocket;
 use lambda_http::{Body, Handler, Request};
 use lambda_runtime::Context;
 use rocket::http::uri::Origin;
-use rocket_lamb::{BasePathBehaviour, RocketExt};
+use rocket_lamb::{BasePathBehaviour, RocketExt, TrailingSlash};
 use std::error::Error;
 use std::fs::File;
 
+#[get("/query/key?<key>")]
+fn get_query_key(key: Option<String>) -> String {
+    format!("{:?}", key)
+}
+
 #[catch(404)]
 fn not_found(req: &rocket::Request) -> String {
     req.uri().to_string()
@@ -20,9 +25,19 @@ fn get_path<'r>(origin: &'r Origin<'r>) -> &'r str {
     origin.path()
 }
 
+#[get("/files/<name>")]
+fn get_file(name: String) -> String {
+    name
+}
+
+#[get("/query")]
+fn get_query<'r>(origin: &'r Origin<'r>) -> &'r str {
+    origin.query().unwrap_or_default()
+}
+
 fn make_rocket() -> rocket::Rocket {
     rocket::ignite()
-        .mount("/", routes![get_path])
+        .mount("/", routes![get_path, get_file, get_query, get_query_key])
         .register(catchers![not_found])
 }
 
@@ -54,6 +69,18 @@ macro_rules! test_case {
 }
 
 test_case!(api_gateway, "path_api_gateway", 200, "/Prod/path/");
+test_case!(
+    api_gateway_china,
+    "path_api_gateway_china",
+    200,
+    "/Prod/path/"
+);
+test_case!(
+    api_gateway_govcloud,
+    "path_api_gateway_govcloud",
+    200,
+    "/Prod/path/"
+);
 test_case!(
     api_gateway_include_base,
     Include,
@@ -61,6 +88,13 @@ test_case!(
     404,
     "/Prod/path/"
 );
+test_case!(
+    api_gateway_include_from_first_request,
+    IncludeFromFirstRequest,
+    "path_api_gateway",
+    404,
+    "/Prod/path/"
+);
 test_case!(
     api_gateway_exclude_base,
     Exclude,
@@ -68,6 +102,13 @@ test_case!(
     200,
     "/path/"
 );
+test_case!(
+    api_gateway_exclude_stage,
+    ExcludeStage,
+    "path_api_gateway",
+    200,
+    "/path/"
+);
 
 test_case!(custom_domain, "path_custom_domain", 200, "/path/");
 test_case!(
@@ -84,6 +125,13 @@ test_case!(
     200,
     "/path/"
 );
+test_case!(
+    custom_domain_exclude_stage_empty_base,
+    ExcludeStage,
+    "path_custom_domain",
+    200,
+    "/path/"
+);
 
 test_case!(
     custom_domain_with_base_path,
@@ -105,6 +153,52 @@ test_case!(
     200,
     "/path/"
 );
+test_case!(
+    custom_domain_with_base_path_exclude_stage,
+    ExcludeStage,
+    "path_custom_domain_with_base",
+    404,
+    "/base-path/path/"
+);
+
+test_case!(
+    multi_segment_base_path,
+    "path_multi_segment_base",
+    200,
+    "/v1/api/path/"
+);
+test_case!(
+    multi_segment_base_path_exclude,
+    Exclude,
+    "path_multi_segment_base",
+    200,
+    "/path/"
+);
+test_case!(
+    multi_segment_base_path_greedy_exclude,
+    Exclude,
+    "path_multi_segment_base_greedy",
+    404,
+    "/nested/api/path/"
+);
+test_case!(
+    multi_segment_base_path_root_exclude,
+    Exclude,
+    "path_multi_segment_base_root",
+    404,
+    "/"
+);
+
+test_case!(encoded_slash, "path_encoded_slash", 200, "a/b");
+test_case!(
+    encoded_slash_exclude,
+    Exclude,
+    "path_encoded_slash",
+    200,
+    "a/b"
+);
+test_case!(encoded_slash_param, "path_encoded_slash_param", 200, "a/b");
+test_case!(encoded_space, "path_encoded_space", 200, "a b");
 
 test_case!(application_load_balancer, "path_alb", 200, "/path/");
 test_case!(
@@ -121,3 +215,325 @@ test_case!(
     200,
     "/path/"
 );
+
+macro_rules! trailing_slash_test_case {
+    ($name:ident, $trailing_slash:ident, $file:expr, $path:expr) => {
+        #[test]
+        fn $name() -> Result<(), Box<dyn Error>> {
+            let mut handler = make_rocket()
+                .lambda()
+                .trailing_slash(TrailingSlash::$trailing_slash)
+                .into_handler();
+
+            let req = get_request($file)?;
+            let res = handler.run(req, Context::default())?;
+
+            assert_eq!(res.status(), 200);
+            assert_eq!(*res.body(), Body::Text($path.to_string()));
+            Ok(())
+        }
+    };
+}
+
+trailing_slash_test_case!(trailing_slash_keep, Keep, "path_custom_domain", "/path/");
+trailing_slash_test_case!(
+    trailing_slash_keep_missing,
+    Keep,
+    "path_no_trailing_slash",
+    "/path"
+);
+trailing_slash_test_case!(trailing_slash_strip, Strip, "path_custom_domain", "/path");
+trailing_slash_test_case!(
+    trailing_slash_strip_missing,
+    Strip,
+    "path_no_trailing_slash",
+    "/path"
+);
+trailing_slash_test_case!(trailing_slash_add, Add, "path_custom_domain", "/path/");
+trailing_slash_test_case!(
+    trailing_slash_add_missing,
+    Add,
+    "path_no_trailing_slash",
+    "/path/"
+);
+
+#[test]
+fn eager_client() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .base_path_behaviour(BasePathBehaviour::Exclude)
+        .eager_client()
+        .into_handler();
+
+    let req = get_request("path_api_gateway")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("/path/".to_string()));
+    Ok(())
+}
+
+#[test]
+#[should_panic]
+fn eager_client_remount() {
+    make_rocket().lambda().eager_client().into_handler();
+}
+
+#[test]
+fn explicit_base_path() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .base_path("/Prod")
+        .eager_client()
+        .into_handler();
+
+    let req = get_request("path_api_gateway")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("/Prod/path/".to_string()));
+    Ok(())
+}
+
+#[test]
+fn explicit_base_path_mismatch() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().base_path("/Dev").into_handler();
+
+    let req = get_request("path_api_gateway")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 404);
+    assert_eq!(*res.body(), Body::Text("/Prod/path/".to_string()));
+    Ok(())
+}
+
+#[test]
+fn reset_client() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("path_api_gateway")?;
+    let res = handler.run(req, Context::default())?;
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("/Prod/path/".to_string()));
+
+    let req = get_request("path_custom_domain_with_base")?;
+    let res = handler.run(req, Context::default())?;
+    assert_eq!(res.status(), 404);
+
+    handler.reset_client(make_rocket());
+
+    let req = get_request("path_custom_domain_with_base")?;
+    let res = handler.run(req, Context::default())?;
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("/base-path/path/".to_string()));
+    Ok(())
+}
+
+#[test]
+fn include_from_first_request() -> Result<(), Box<dyn Error>> {
+    let mut handler = rocket::ignite()
+        .mount("/Prod", routes![get_path])
+        .lambda()
+        .base_path_behaviour(BasePathBehaviour::IncludeFromFirstRequest)
+        .into_handler();
+
+    let req = get_request("path_api_gateway")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("/Prod/path/".to_string()));
+    Ok(())
+}
+
+#[test]
+fn include_explicit_base_path() -> Result<(), Box<dyn Error>> {
+    let mut handler = rocket::ignite()
+        .mount("/v1", routes![get_path])
+        .lambda()
+        .base_path_behaviour(BasePathBehaviour::IncludeFromFirstRequest)
+        .base_path("/v1")
+        .eager_client()
+        .into_handler();
+
+    let req = get_request("path_api_gateway")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("/v1/path/".to_string()));
+    Ok(())
+}
+
+#[test]
+fn base_path_segments() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().base_path_segments(2).into_handler();
+
+    let req = get_request("path_stage_like_base")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("/api/Prod/path/".to_string()));
+    Ok(())
+}
+
+#[test]
+fn base_path_segments_exclude() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .base_path_behaviour(BasePathBehaviour::Exclude)
+        .base_path_segments(2)
+        .into_handler();
+
+    let req = get_request("path_stage_like_base")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("/path/".to_string()));
+    Ok(())
+}
+
+#[test]
+fn base_path_segments_ignores_resource_path() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .base_path_behaviour(BasePathBehaviour::Exclude)
+        .base_path_segments(0)
+        .into_handler();
+
+    let req = get_request("path_resource_mismatch")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("/path/".to_string()));
+    Ok(())
+}
+
+#[test]
+fn base_path_segments_too_few() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .base_path_segments(4)
+        .error_response(400)
+        .into_handler();
+
+    let req = get_request("path_stage_like_base")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 400);
+    assert_eq!(
+        *res.body(),
+        Body::Text(
+            "could not transform request: could not find 4 base path segments in path '/api/Prod/path/'"
+                .to_string()
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn resource_path_mismatch() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().error_response(400).into_handler();
+
+    let req = get_request("path_resource_mismatch")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 400);
+    assert_eq!(
+        *res.body(),
+        Body::Text(
+            "could not transform request: could not find segment '/other' in path '/path/'"
+                .to_string()
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn query_parameters_sorted() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("query_order")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("a=2&m=3&z=1".to_string()));
+    Ok(())
+}
+
+#[test]
+fn query_parameter_without_value() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("query_flag")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("flag&key=value".to_string()));
+    Ok(())
+}
+
+#[test]
+fn query_parameter_empty_value() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("query_empty_value")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("key".to_string()));
+    Ok(())
+}
+
+#[test]
+fn query_guard_without_value() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("query_key_flag")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("Some(\"\")".to_string()));
+    Ok(())
+}
+
+#[test]
+fn query_guard_empty_value() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("query_key_empty_value")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("Some(\"\")".to_string()));
+    Ok(())
+}
+
+#[test]
+fn query_parameter_repeated() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("query_repeated")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("tag=a&tag=b".to_string()));
+    Ok(())
+}
+
+#[test]
+fn remounted_routes() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+    assert!(handler.routes().is_none());
+
+    let req = get_request("path_custom_domain_with_base")?;
+    handler.run(req, Context::default())?;
+
+    let routes: Vec<String> = handler
+        .routes()
+        .unwrap()
+        .iter()
+        .map(|route| route.uri.path().to_owned())
+        .collect();
+    assert!(routes.contains(&"/path".to_owned()));
+    assert!(routes.contains(&"/base-path/path".to_owned()));
+    Ok(())
+}
@@ -5,13 +5,33 @@ extern crate rocket;
 
 use lambda_http::{Body, Handler, Request, Response};
 use lambda_runtime::Context;
-use rocket_lamb::{ResponseType, RocketExt};
+use rocket::config::{Config, Environment};
+use rocket::fairing::AdHoc;
+use rocket::http::{ContentType, Cookie, Cookies, Header, Status};
+use rocket::request::{self, FromRequest};
+use rocket::response::content::Content;
+use rocket::response::status;
+use rocket::response::Redirect;
+use rocket::response::Stream;
+use rocket::{Outcome, State};
+use rocket_lamb::{CorsPreflight, ResponseType, RocketExt, RocketLambError};
+use std::collections::HashMap;
 use std::error::Error;
 use std::fs::File;
+use std::io::Cursor;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[catch(404)]
 fn not_found() {}
 
+#[catch(404)]
+fn not_found_json() -> Content<&'static str> {
+    Content(ContentType::JSON, "{\"error\":\"not found\"}")
+}
+
 #[post("/upper/<path>?<query>", data = "<body>")]
 fn upper(path: String, query: String, body: String) -> String {
     format!(
@@ -27,9 +47,221 @@ fn binary() -> &'static [u8] {
     &[200, 201, 202]
 }
 
+#[post("/echo", data = "<body>")]
+fn echo(body: Vec<u8>) -> Vec<u8> {
+    body
+}
+
+#[get("/cookies")]
+fn cookies(mut cookies: Cookies) {
+    cookies.add(Cookie::new("first", "1"));
+    cookies.add(Cookie::new("second", "2"));
+}
+
+#[get("/cookie/<name>")]
+fn cookie(name: String, cookies: Cookies) -> Option<String> {
+    cookies.get(&name).map(|c| c.value().to_owned())
+}
+
+struct Headers(HashMap<String, String>);
+
+impl<'a, 'r> FromRequest<'a, 'r> for Headers {
+    type Error = ();
+
+    fn from_request(request: &'a rocket::Request<'r>) -> request::Outcome<Self, ()> {
+        let headers = request
+            .headers()
+            .iter()
+            .map(|h| (h.name().to_lowercase(), h.value().to_string()))
+            .collect();
+        Outcome::Success(Headers(headers))
+    }
+}
+
+#[get("/header/<name>")]
+fn header(name: String, headers: Headers) -> Option<String> {
+    headers.0.get(&name.to_lowercase()).cloned()
+}
+
+#[get("/redirect")]
+fn redirect(headers: Headers) -> Redirect {
+    let scheme = headers
+        .0
+        .get("x-forwarded-proto")
+        .map_or("http", String::as_str);
+    let host = headers.0.get("host").map_or("localhost", String::as_str);
+    Redirect::found(format!("{}://{}/target", scheme, host))
+}
+
+#[get("/remote")]
+fn remote(remote: SocketAddr) -> String {
+    remote.ip().to_string()
+}
+
+#[route(TRACE, path = "/trace")]
+fn trace() -> &'static str {
+    "trace"
+}
+
+#[get("/image")]
+fn image() -> Content<&'static str> {
+    Content(ContentType::PNG, "valid UTF-8")
+}
+
+#[get("/content-length")]
+fn content_length() -> rocket::Response<'static> {
+    rocket::Response::build()
+        .header(ContentType::Binary)
+        .raw_header("Content-Length", "3")
+        .raw_header("Transfer-Encoding", "identity")
+        .sized_body(Cursor::new(vec![200, 201, 202]))
+        .finalize()
+}
+
+#[get("/no-content")]
+fn no_content() -> Status {
+    Status::NoContent
+}
+
+#[get("/no-content-with-body")]
+fn no_content_with_body() -> status::Custom<&'static str> {
+    status::Custom(Status::NoContent, "")
+}
+
+#[get("/json-api")]
+fn json_api() -> rocket::Response<'static> {
+    rocket::Response::build()
+        .raw_header("Content-Type", "application/vnd.api+json ; charset=utf-8")
+        .sized_body(Cursor::new("{\"data\":null}"))
+        .finalize()
+}
+
+#[get("/response-type/<response_type>")]
+fn response_type_header(response_type: String) -> rocket::Response<'static> {
+    rocket::Response::build()
+        .header(ContentType::Plain)
+        .raw_header("X-Lambda-Response-Type", response_type)
+        .sized_body(Cursor::new("hello"))
+        .finalize()
+}
+
+#[get("/etag")]
+fn etag(headers: Headers) -> rocket::Response<'static> {
+    let mut builder = rocket::Response::build();
+    builder
+        .raw_header("ETag", "\"abc\"")
+        .raw_header("Cache-Control", "max-age=60");
+    if headers.0.get("if-none-match").map(String::as_str) == Some("\"abc\"") {
+        builder.status(Status::NotModified);
+    } else {
+        builder
+            .header(ContentType::Plain)
+            .sized_body(Cursor::new("etag"));
+    }
+    builder.finalize()
+}
+
+#[get("/stream")]
+fn stream() -> Content<Stream<Cursor<Vec<u8>>>> {
+    Content(
+        ContentType::Binary,
+        Stream::chunked(Cursor::new(stream_data()), 4096),
+    )
+}
+
+fn stream_data() -> Vec<u8> {
+    (0..1024 * 1024).map(|i| (i % 251) as u8).collect()
+}
+
+#[get("/untyped")]
+fn untyped() -> rocket::Response<'static> {
+    rocket::Response::build()
+        .sized_body(Cursor::new("untyped"))
+        .finalize()
+}
+
+#[get("/status/<code>")]
+fn status_code(code: u16) -> status::Custom<&'static str> {
+    status::Custom(Status::raw(code), "status")
+}
+
+#[get("/multipart")]
+fn multipart() -> rocket::Response<'static> {
+    rocket::Response::build()
+        .raw_header("Content-Type", "Multipart/Mixed; boundary=\"--x;y\"")
+        .sized_body(Cursor::new("--x;y--"))
+        .finalize()
+}
+
+#[get("/download")]
+fn download() -> rocket::Response<'static> {
+    rocket::Response::build()
+        .header(ContentType::CSV)
+        .raw_header("Content-Disposition", "attachment; filename=\"data.csv\"")
+        .sized_body(Cursor::new("a,b\n1,2\n"))
+        .finalize()
+}
+
+#[get("/empty-binary")]
+fn empty_binary() -> Content<Vec<u8>> {
+    Content(ContentType::Binary, Vec::new())
+}
+
+struct ManagedState(&'static str);
+
+#[get("/managed")]
+fn managed(state: State<ManagedState>) -> &'static str {
+    state.0
+}
+
+#[post("/upload", format = "multipart/form-data", data = "<body>")]
+fn upload(body: Vec<u8>) -> Vec<u8> {
+    body
+}
+
+fn multipart_body() -> Vec<u8> {
+    let mut body = b"--boundary123\r\nContent-Disposition: form-data; name=\"file\"; filename=\"pixel.gif\"\r\nContent-Type: image/gif\r\n\r\n".to_vec();
+    body.extend_from_slice(include_bytes!("static/pixel.gif"));
+    body.extend_from_slice(b"\r\n--boundary123--\r\n");
+    body
+}
+
+#[get("/panic")]
+fn panic() {
+    panic!("route panicked")
+}
+
 fn make_rocket() -> rocket::Rocket {
     rocket::ignite()
-        .mount("/", routes![upper, binary])
+        .mount(
+            "/",
+            routes![
+                upper,
+                binary,
+                image,
+                echo,
+                cookies,
+                header,
+                redirect,
+                remote,
+                trace,
+                content_length,
+                no_content,
+                no_content_with_body,
+                panic,
+                json_api,
+                response_type_header,
+                etag,
+                stream,
+                untyped,
+                status_code,
+                cookie,
+                multipart,
+                download,
+                empty_binary,
+                upload
+            ],
+        )
         .register(catchers![not_found])
 }
 
@@ -51,6 +283,18 @@ fn ok_auto_text() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn ok_special_query_characters() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("upper_special_query")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("ONE, A%B+C D, THREE".to_string()));
+    Ok(())
+}
+
 #[test]
 fn ok_auto_binary() -> Result<(), Box<dyn Error>> {
     let mut handler = make_rocket().lambda().into_handler();
@@ -64,6 +308,241 @@ fn ok_auto_binary() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn ok_auto_binary_content_type() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("image")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_header(&res, "content-type", "image/png");
+    assert_eq!(*res.body(), Body::Binary(b"valid UTF-8".to_vec()));
+    Ok(())
+}
+
+#[test]
+fn ok_auto_text_suffix_content_type() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("json_api")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("{\"data\":null}".to_string()));
+    Ok(())
+}
+
+#[test]
+fn response_type_header_binary() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("response_type_binary")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Binary(b"hello".to_vec()));
+    assert!(res.headers().get("x-lambda-response-type").is_none());
+    Ok(())
+}
+
+#[test]
+fn response_type_header_overrides_config() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .default_response_type(ResponseType::Binary)
+        .response_type("text/plain", ResponseType::Binary)
+        .into_handler();
+
+    let req = get_request("response_type_text")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("hello".to_string()));
+    assert!(res.headers().get("x-lambda-response-type").is_none());
+    Ok(())
+}
+
+#[test]
+fn quoted_content_type_parameter() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .response_type("multipart/mixed", ResponseType::Text)
+        .into_handler();
+
+    let req = get_request("multipart")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("--x;y--".to_string()));
+    Ok(())
+}
+
+#[test]
+fn quoted_accept_parameter() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .api_gateway_binary_media_types(&["image/*"])
+        .into_handler();
+
+    let req = get_request("accept_quoted")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(
+        *res.body(),
+        Body::Text("text/html; x=\"a,image/png\"".to_string())
+    );
+    Ok(())
+}
+
+#[test]
+fn binary_attachment() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .default_response_type(ResponseType::Text)
+        .binary_attachments(true)
+        .into_handler();
+
+    let req = get_request("download")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_header(
+        &res,
+        "content-disposition",
+        "attachment; filename=\"data.csv\"",
+    );
+    assert_eq!(*res.body(), Body::Binary(b"a,b\n1,2\n".to_vec()));
+    Ok(())
+}
+
+#[test]
+fn text_attachment() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("download")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("a,b\n1,2\n".to_string()));
+    Ok(())
+}
+
+#[test]
+fn ok_auto_empty_binary() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("empty_binary")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Binary(Vec::new()));
+    Ok(())
+}
+
+#[test]
+fn ok_text_empty_binary() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .default_response_type(ResponseType::Text)
+        .into_handler();
+
+    let req = get_request("empty_binary")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text(String::new()));
+    Ok(())
+}
+
+#[test]
+fn binary_media_type_accepted() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .api_gateway_binary_media_types(&["image/*"])
+        .into_handler();
+
+    let req = get_request("accept_binary")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Binary(b"image/png".to_vec()));
+    Ok(())
+}
+
+#[test]
+fn binary_media_type_not_accepted() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .api_gateway_binary_media_types(&["application/octet-stream"])
+        .into_handler();
+
+    let req = get_request("accept_binary")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("image/png".to_string()));
+    Ok(())
+}
+
+#[test]
+fn binary_media_type_not_first_accepted() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .api_gateway_binary_media_types(&["image/*"])
+        .into_handler();
+
+    let req = get_request("accept_binary_not_first")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(
+        *res.body(),
+        Body::Text("text/html,image/webp,*/*".to_string())
+    );
+    Ok(())
+}
+
+#[test]
+fn response_type_resolver() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .response_type("image/png", ResponseType::Binary)
+        .response_type_resolver(|res| {
+            if res.content_type() == Some(ContentType::PNG) {
+                Some(ResponseType::Text)
+            } else {
+                None
+            }
+        })
+        .into_handler();
+
+    let req = get_request("image")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_header(&res, "content-type", "image/png");
+    assert_eq!(*res.body(), Body::Text("valid UTF-8".to_string()));
+    Ok(())
+}
+
+#[test]
+fn response_type_resolver_fallback() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .response_type("image/png", ResponseType::Text)
+        .response_type_resolver(|_| None)
+        .into_handler();
+
+    let req = get_request("image")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("valid UTF-8".to_string()));
+    Ok(())
+}
+
 #[test]
 fn ok_default_binary() -> Result<(), Box<dyn Error>> {
     let mut handler = make_rocket()
@@ -103,20 +582,1321 @@ fn ok_type_binary() -> Result<(), Box<dyn Error>> {
 }
 
 #[test]
-fn request_not_found() -> Result<(), Box<dyn Error>> {
+fn binary_request_body() -> Result<(), Box<dyn Error>> {
     let mut handler = make_rocket().lambda().into_handler();
 
-    let req = get_request("not_found")?;
+    let req = get_request("binary_body")?;
     let res = handler.run(req, Context::default())?;
 
-    assert_eq!(res.status(), 404);
-    assert_eq!(res.headers().contains_key("content-type"), false);
-    assert!(res.body().is_empty(), "Response body should be empty");
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Binary(vec![200, 201, 202]));
     Ok(())
 }
 
-fn assert_header(res: &Response<Body>, name: &str, value: &str) {
-    let values = res.headers().get_all(name).iter().collect::<Vec<_>>();
-    assert_eq!(values.len(), 1, "Header {} should have 1 value", name);
-    assert_eq!(values[0], value);
+#[test]
+fn multipart_upload() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("multipart_upload")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Binary(multipart_body()));
+    Ok(())
+}
+
+#[test]
+fn multiple_set_cookie_headers() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("cookies")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    let mut values = res
+        .headers()
+        .get_all("set-cookie")
+        .iter()
+        .map(|v| v.to_str())
+        .collect::<Result<Vec<_>, _>>()?;
+    values.sort();
+    assert_eq!(values.len(), 2, "Header set-cookie should have 2 values");
+    assert!(values[0].starts_with("first=1"));
+    assert!(values[1].starts_with("second=2"));
+    Ok(())
+}
+
+#[test]
+fn ok_type_wildcard_binary() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .response_type("text/*", ResponseType::Binary)
+        .into_handler();
+
+    let req = get_request("upper")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_header(&res, "content-type", "text/plain; charset=utf-8");
+    assert_eq!(
+        *res.body(),
+        Body::Binary("ONE, TWO, THREE".to_owned().into_bytes())
+    );
+    Ok(())
+}
+
+#[test]
+fn invalid_request_error() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("invalid_header")?;
+    let res = handler.run(req, Context::default());
+
+    assert!(res.is_err(), "Handler should return an error");
+    Ok(())
+}
+
+#[test]
+fn invalid_request_error_response() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().error_response(500).into_handler();
+
+    let req = get_request("invalid_header")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 500);
+    assert_header(&res, "content-type", "text/plain; charset=utf-8");
+    assert_eq!(
+        *res.body(),
+        Body::Text("could not transform request: invalid value for header 'x-invalid'".to_string())
+    );
+    Ok(())
+}
+
+#[test]
+fn unsupported_event_error() -> Result<(), Box<dyn Error>> {
+    let handler = make_rocket().lambda().into_handler();
+
+    let req = Request::new(Body::Empty);
+    let res = handler.try_handle(req, Context::default());
+
+    match res {
+        Err(RocketLambError::UnsupportedEvent(_)) => Ok(()),
+        _ => panic!("Handler should return an UnsupportedEvent error"),
+    }
+}
+
+#[test]
+fn launch_error_type() -> Result<(), Box<dyn Error>> {
+    let handler = rocket::ignite()
+        .mount("/", routes![binary])
+        .mount("/", routes![binary])
+        .lambda()
+        .into_handler();
+
+    let req = get_request("binary")?;
+    let res = handler.try_handle(req, Context::default());
+
+    match res {
+        Err(RocketLambError::LaunchFailed(_)) => Ok(()),
+        _ => panic!("Handler should return a LaunchFailed error"),
+    }
+}
+
+#[test]
+fn unsupported_event() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().error_response(400).into_handler();
+
+    let req = Request::new(Body::Empty);
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 400);
+    assert_eq!(
+        *res.body(),
+        Body::Text(
+            "unsupported event: request has no API Gateway or Application Load Balancer request context"
+                .to_string()
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn malformed_uri() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("malformed_path")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 400);
+    assert_eq!(*res.body(), Body::Empty);
+    Ok(())
+}
+
+#[test]
+fn special_query_parameter_name() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("upper_special_query_name")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("ONE, TWO, THREE".to_string()));
+    Ok(())
+}
+
+#[test]
+fn launch_error() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .mount("/", routes![binary])
+        .lambda()
+        .into_handler();
+
+    let req = get_request("binary")?;
+    let res = handler.run(req, Context::default());
+
+    assert!(res.is_err(), "Handler should return an error");
+    Ok(())
+}
+
+#[test]
+fn launch_error_response() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .mount("/", routes![binary])
+        .lambda()
+        .error_response(500)
+        .into_handler();
+
+    let req = get_request("binary")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 500);
+    assert_eq!(
+        *res.body(),
+        Body::Text("could not launch Rocket: route collisions detected".to_string())
+    );
+    Ok(())
+}
+
+#[test]
+fn on_request_adds_header() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .on_request(|req, local_req| {
+            let port = req.headers()["x-forwarded-port"].to_str().unwrap();
+            local_req.add_header(Header::new("X-Injected", port.to_owned()));
+        })
+        .into_handler();
+
+    let req = get_request("header_x_injected")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("443".to_string()));
+    Ok(())
+}
+
+#[test]
+fn on_response_adds_header() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .on_response(|res| {
+            res.headers_mut()
+                .insert("x-on-response", "true".parse().unwrap());
+        })
+        .into_handler();
+
+    let req = get_request("upper")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_header(&res, "x-on-response", "true");
+    assert_eq!(*res.body(), Body::Text("ONE, TWO, THREE".to_string()));
+    Ok(())
+}
+
+#[test]
+fn remote_source_ip() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("remote")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("203.0.113.7".to_string()));
+    Ok(())
+}
+
+#[test]
+fn remote_source_ip_alb() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("remote_alb")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("203.0.113.7".to_string()));
+    Ok(())
+}
+
+#[test]
+fn invalid_text_error() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .response_type("application/octet-stream", ResponseType::Text)
+        .into_handler();
+
+    let req = get_request("binary")?;
+    let res = handler.run(req, Context::default());
+
+    assert!(res.is_err(), "Handler should return an error");
+    Ok(())
+}
+
+#[test]
+fn invalid_text_fallback_to_binary() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .response_type("application/octet-stream", ResponseType::Text)
+        .text_fallback_to_binary(true)
+        .into_handler();
+
+    let req = get_request("binary")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_header(&res, "content-type", "application/octet-stream");
+    assert_eq!(*res.body(), Body::Binary(vec![200, 201, 202]));
+    Ok(())
+}
+
+#[test]
+fn max_response_size_exceeded() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().max_response_size(10).into_handler();
+
+    let req = get_request("upper")?;
+    let res = handler.run(req, Context::default());
+
+    assert!(res.is_err(), "Handler should return an error");
+    Ok(())
+}
+
+#[test]
+fn max_response_size_encoded_binary() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().max_response_size(4).into_handler();
+
+    let req = get_request("binary")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Binary(vec![200, 201, 202]));
+    Ok(())
+}
+
+#[test]
+fn debug_echo() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .debug_echo_path("/__lamb_debug")
+        .into_handler();
+
+    let req = get_request("debug_echo")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_header(&res, "content-type", "application/json");
+    let body: serde_json::Value = match res.body() {
+        Body::Text(s) => serde_json::from_str(s)?,
+        body => panic!("unexpected body: {:?}", body),
+    };
+    assert_eq!(body["method"], "GET");
+    assert_eq!(body["path"], "/base-path/__lamb_debug");
+    assert_eq!(body["query"], "q=1");
+    let headers = body["headers"].as_array().unwrap();
+    assert!(headers.contains(&serde_json::json!({ "name": "host", "value": "example.com" })));
+    Ok(())
+}
+
+#[test]
+fn attach_fairing() -> Result<(), Box<dyn Error>> {
+    let mut handler = rocket::ignite()
+        .mount("/", routes![managed])
+        .attach(AdHoc::on_attach("Managed State", |rocket| {
+            Ok(rocket.manage(ManagedState("attached")))
+        }))
+        .lambda()
+        .into_handler();
+
+    let req = get_request("managed")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("attached".to_string()));
+    Ok(())
+}
+
+#[test]
+fn truncated_text_response() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .truncate_oversized_responses(14)
+        .into_handler();
+
+    let req = get_request("upper")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("ONE, TWO, THRE".to_string()));
+    assert_header(&res, "x-lamb-truncated", "true");
+    Ok(())
+}
+
+#[test]
+fn truncated_binary_response() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .truncate_oversized_responses(1000)
+        .into_handler();
+
+    let req = get_request("stream")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Binary(stream_data()[..750].to_vec()));
+    assert_header(&res, "x-lamb-truncated", "true");
+    Ok(())
+}
+
+#[test]
+fn response_not_truncated() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .truncate_oversized_responses(15)
+        .into_handler();
+
+    let req = get_request("upper")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("ONE, TWO, THREE".to_string()));
+    assert!(res.headers().get("x-lamb-truncated").is_none());
+    Ok(())
+}
+
+#[test]
+fn no_port_binding() -> Result<(), Box<dyn Error>> {
+    // Rocket is configured to use a port that is already in use, which would fail if it attempted to bind to it
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let config = Config::build(Environment::Development)
+        .address("127.0.0.1")
+        .port(listener.local_addr()?.port())
+        .finalize()?;
+    let mut handler = rocket::custom(config)
+        .mount("/", routes![upper])
+        .lambda()
+        .eager_client()
+        .into_handler();
+
+    let req = get_request("upper")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("ONE, TWO, THREE".to_string()));
+    Ok(())
+}
+
+#[test]
+fn health_check() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .health_check_path("/_health")
+        .into_handler();
+
+    let req = get_request("health")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert!(res.body().is_empty(), "Response body should be empty");
+    Ok(())
+}
+
+#[test]
+fn method_not_allowed() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("unknown_method")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 405);
+    assert_header(
+        &res,
+        "allow",
+        "GET, PUT, POST, DELETE, OPTIONS, HEAD, TRACE, CONNECT, PATCH",
+    );
+    assert!(res.body().is_empty(), "Response body should be empty");
+    Ok(())
+}
+
+#[test]
+fn trace_method() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("trace")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("trace".to_string()));
+    Ok(())
+}
+
+#[test]
+fn connect_method_not_found() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("connect")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 404);
+    assert!(res.body().is_empty(), "Response body should be empty");
+    Ok(())
+}
+
+#[test]
+fn map_status() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .map_status(404, |mut res| {
+            res.headers_mut().insert("x-mapped", "404".parse().unwrap());
+            res
+        })
+        .map_status(200, |_| panic!("200 should not be mapped"))
+        .into_handler();
+
+    let req = get_request("not_found")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 404);
+    assert_header(&res, "x-mapped", "404");
+    Ok(())
+}
+
+#[test]
+fn forwarded_proto_added() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("header_x_forwarded_proto")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("https".to_string()));
+    Ok(())
+}
+
+#[test]
+fn redirect_uses_https() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("redirect")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 302);
+    assert_header(&res, "location", "https://example.com/target");
+    Ok(())
+}
+
+#[test]
+fn redirect_uses_forwarded_proto() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .forwarded_proto("http")
+        .into_handler();
+
+    let req = get_request("redirect")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 302);
+    assert_header(&res, "location", "http://example.com/target");
+    Ok(())
+}
+
+#[test]
+fn request_header_stripped() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .strip_request_headers(&["x-forwarded-port"])
+        .into_handler();
+
+    let req = get_request("header_x_forwarded_port")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 404);
+    Ok(())
+}
+
+#[test]
+fn request_header_not_stripped() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .strip_request_headers(&["X-Forwarded-Proto"])
+        .into_handler();
+
+    let req = get_request("header_x_forwarded_port")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("443".to_string()));
+    Ok(())
+}
+
+#[test]
+fn request_header_stripped_by_prefix() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .strip_request_headers(&["X-Forwarded-*"])
+        .into_handler();
+
+    let req = get_request("header_x_forwarded_proto")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 404);
+    Ok(())
+}
+
+#[test]
+fn response_header_stripped() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .strip_response_headers(&["server"])
+        .into_handler();
+
+    let req = get_request("upper")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert!(res.headers().get("server").is_none());
+    Ok(())
+}
+
+#[test]
+fn response_header_not_stripped() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("upper")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_header(&res, "server", "Rocket");
+    Ok(())
+}
+
+#[test]
+fn cors_preflight_default() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .cors_preflight(CorsPreflight::new())
+        .into_handler();
+
+    let req = get_request("cors_preflight")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 204);
+    assert_eq!(*res.body(), Body::Empty);
+    assert_header(&res, "access-control-allow-origin", "*");
+    assert_header(
+        &res,
+        "access-control-allow-methods",
+        "GET, HEAD, POST, PUT, PATCH, DELETE",
+    );
+    assert_header(&res, "access-control-allow-headers", "*");
+    assert!(res.headers().get("access-control-max-age").is_none());
+    Ok(())
+}
+
+#[test]
+fn cors_preflight_on_response() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .cors_preflight(CorsPreflight::new())
+        .on_response(|res| {
+            res.headers_mut()
+                .insert("x-on-response", "true".parse().unwrap());
+        })
+        .into_handler();
+
+    let req = get_request("cors_preflight")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 204);
+    assert_header(&res, "access-control-allow-origin", "*");
+    assert_header(&res, "x-on-response", "true");
+    Ok(())
+}
+
+#[test]
+fn cors_preflight_allowed_origin() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .cors_preflight(
+            CorsPreflight::new()
+                .allowed_origins(&["https://example.com"])
+                .allowed_methods(&["POST"])
+                .allowed_headers(&["Content-Type"])
+                .max_age(Duration::from_secs(600)),
+        )
+        .into_handler();
+
+    let req = get_request("cors_preflight")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 204);
+    assert_header(&res, "access-control-allow-origin", "https://example.com");
+    assert_header(&res, "access-control-allow-methods", "POST");
+    assert_header(&res, "access-control-allow-headers", "Content-Type");
+    assert_header(&res, "access-control-max-age", "600");
+    assert_header(&res, "vary", "Origin");
+    Ok(())
+}
+
+#[test]
+fn cors_preflight_disallowed_origin() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .cors_preflight(CorsPreflight::new().allowed_origins(&["https://other.example.com"]))
+        .into_handler();
+
+    let req = get_request("cors_preflight")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 404);
+    assert!(res.headers().get("access-control-allow-origin").is_none());
+    Ok(())
+}
+
+#[test]
+fn cors_preflight_not_configured() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("cors_preflight")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 404);
+    Ok(())
+}
+
+#[test]
+fn default_content_type() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .default_content_type("application/octet-stream")
+        .into_handler();
+
+    let req = get_request("untyped")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_header(&res, "content-type", "application/octet-stream");
+    assert_eq!(*res.body(), Body::Binary(b"untyped".to_vec()));
+    Ok(())
+}
+
+#[test]
+fn no_default_content_type() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("untyped")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert!(res.headers().get("content-type").is_none());
+    assert_eq!(*res.body(), Body::Text("untyped".to_string()));
+    Ok(())
+}
+
+#[test]
+fn default_content_type_not_set_without_body() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .default_content_type("application/octet-stream")
+        .into_handler();
+
+    let req = get_request("no_content")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 204);
+    assert!(res.headers().get("content-type").is_none());
+    Ok(())
+}
+
+#[test]
+fn request_too_large() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().max_request_size(4).into_handler();
+
+    let req = get_request("upper")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 413);
+    assert_eq!(*res.body(), Body::Empty);
+    Ok(())
+}
+
+#[test]
+fn request_within_max_size() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().max_request_size(5).into_handler();
+
+    let req = get_request("upper")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("ONE, TWO, THREE".to_string()));
+    Ok(())
+}
+
+#[test]
+fn too_many_requests_status() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("status_429")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 429);
+    assert_eq!(*res.body(), Body::Text("status".to_string()));
+    Ok(())
+}
+
+#[test]
+fn non_standard_status() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("status_599")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 599);
+    assert_eq!(*res.body(), Body::Text("status".to_string()));
+    Ok(())
+}
+
+#[test]
+fn response_type_for_status() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .default_response_type(ResponseType::Binary)
+        .response_type_for_status(500..=599, ResponseType::Text)
+        .into_handler();
+
+    let req = get_request("status_599")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 599);
+    assert_eq!(*res.body(), Body::Text("status".to_string()));
+
+    let req = get_request("status_429")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 429);
+    assert_eq!(*res.body(), Body::Binary(b"status".to_vec()));
+    Ok(())
+}
+
+#[test]
+fn response_type_for_status_overrides_header() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .response_type_for_status(200..=299, ResponseType::Text)
+        .into_handler();
+
+    let req = get_request("response_type_binary")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("hello".to_string()));
+    Ok(())
+}
+
+#[test]
+fn direct_invocation() -> Result<(), Box<dyn Error>> {
+    let handler = make_rocket()
+        .lambda()
+        .direct_invocation_path("/echo")
+        .into_handler();
+
+    let event = serde_json::json!({ "orderId": 123 });
+    let res = handler.handle_direct_invocation(event, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Binary(b"{\"orderId\":123}".to_vec()));
+    Ok(())
+}
+
+#[test]
+fn decompress_gzip_request() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().decompress_requests().into_handler();
+
+    let req = get_request("gzip_body")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Binary(b"{\"hello\":\"world\"}".to_vec()));
+    Ok(())
+}
+
+#[test]
+fn decompress_deflate_request() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().decompress_requests().into_handler();
+
+    let req = get_request("deflate_body")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Binary(b"{\"hello\":\"world\"}".to_vec()));
+    Ok(())
+}
+
+#[test]
+fn decompress_corrupt_request() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().decompress_requests().into_handler();
+
+    let req = get_request("gzip_body_corrupt")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 400);
+    assert_eq!(*res.body(), Body::Empty);
+    Ok(())
+}
+
+#[test]
+fn decompressed_request_too_large() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .decompress_requests()
+        .max_request_size(1000)
+        .into_handler();
+
+    let req = get_request("gzip_body_large")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 413);
+    assert_eq!(*res.body(), Body::Empty);
+    Ok(())
+}
+
+#[test]
+fn decompressed_request_within_limit() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .decompress_requests()
+        .max_request_size(100_000)
+        .into_handler();
+
+    let req = get_request("gzip_body_large")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Binary(vec![b'0'; 100_000]));
+    Ok(())
+}
+
+#[test]
+fn tracked_client() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().tracked_client(true).into_handler();
+
+    let req = get_request("cookies")?;
+    handler.run(req, Context::default())?;
+    let req = get_request("cookie_first")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("1".to_string()));
+    Ok(())
+}
+
+#[test]
+fn untracked_client() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("cookies")?;
+    handler.run(req, Context::default())?;
+    let req = get_request("cookie_first")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 404);
+    Ok(())
+}
+
+#[test]
+fn content_length_removed() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("content_length")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert!(!res.headers().contains_key("content-length"));
+    assert!(!res.headers().contains_key("transfer-encoding"));
+    assert_eq!(*res.body(), Body::Binary(vec![200, 201, 202]));
+    Ok(())
+}
+
+#[test]
+fn no_content_empty_body() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("no_content")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 204);
+    assert_eq!(*res.body(), Body::Empty);
+    Ok(())
+}
+
+#[test]
+fn no_content_with_body_empty_body() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .default_response_type(ResponseType::Text)
+        .into_handler();
+
+    let req = get_request("no_content_with_body")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 204);
+    assert_eq!(*res.body(), Body::Empty);
+    Ok(())
+}
+
+#[test]
+fn inline_event() -> Result<(), Box<dyn Error>> {
+    let handler = make_rocket().lambda().into_handler();
+
+    let req = lambda_http::request::from_str(
+        r#"{
+            "requestContext": {
+                "elb": {
+                    "targetGroupArn": "arn:aws:elasticloadbalancing:eu-west-1:123456789123:targetgroup/example/0123456789abcdef"
+                }
+            },
+            "path": "/upper/one",
+            "httpMethod": "POST",
+            "headers": { "Host": "example.com" },
+            "queryStringParameters": { "query": "two" },
+            "body": "three",
+            "isBase64Encoded": false
+        }"#,
+    )?;
+    let res = handler.handle(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("ONE, TWO, THREE".to_string()));
+    Ok(())
+}
+
+#[test]
+fn shared_handler() -> Result<(), Box<dyn Error>> {
+    let handler = Arc::new(make_rocket().lambda().into_handler());
+
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let handler = handler.clone();
+            thread::spawn(move || {
+                let req = get_request("upper").unwrap();
+                handler.handle(req, Context::default()).unwrap()
+            })
+        })
+        .collect();
+
+    for thread in threads {
+        let res = thread.join().unwrap();
+        assert_eq!(res.status(), 200);
+        assert_eq!(*res.body(), Body::Text("ONE, TWO, THREE".to_string()));
+    }
+    Ok(())
+}
+
+#[test]
+fn deadline_exceeded() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .respect_deadline(Duration::from_secs(1))
+        .into_handler();
+
+    let req = get_request("upper")?;
+    let ctx = Context {
+        deadline: now_ms() + 500,
+        ..Context::default()
+    };
+    let res = handler.run(req, ctx)?;
+
+    assert_eq!(res.status(), 503);
+    assert_header(&res, "retry-after", "1");
+    assert_eq!(*res.body(), Body::Empty);
+    Ok(())
+}
+
+#[test]
+fn deadline_not_exceeded() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .respect_deadline(Duration::from_secs(1))
+        .into_handler();
+
+    let req = get_request("upper")?;
+    let ctx = Context {
+        deadline: now_ms() + 60_000,
+        ..Context::default()
+    };
+    let res = handler.run(req, ctx)?;
+
+    assert_eq!(res.status(), 200);
+    Ok(())
+}
+
+#[test]
+fn deadline_exceeded_custom_margin() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .respect_deadline(Duration::from_secs(10))
+        .into_handler();
+
+    let req = get_request("upper")?;
+    let ctx = Context {
+        deadline: now_ms() + 5_000,
+        ..Context::default()
+    };
+    let res = handler.run(req, ctx)?;
+
+    assert_eq!(res.status(), 503);
+    Ok(())
+}
+
+#[test]
+fn mutate_rocket() -> Result<(), Box<dyn Error>> {
+    let mut handler = rocket::ignite()
+        .lambda()
+        .mutate_rocket(|rocket| rocket.mount("/", routes![binary]))
+        .into_handler();
+
+    let req = get_request("binary")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Binary(vec![200, 201, 202]));
+    Ok(())
+}
+
+#[test]
+fn not_modified() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("etag_match")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 304);
+    assert_eq!(*res.body(), Body::Empty);
+    assert_header(&res, "etag", "\"abc\"");
+    assert_header(&res, "cache-control", "max-age=60");
+    Ok(())
+}
+
+#[test]
+fn modified() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("etag_no_match")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("etag".to_string()));
+    assert_header(&res, "etag", "\"abc\"");
+    Ok(())
+}
+
+#[test]
+fn chunked_response() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("stream")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Binary(stream_data()));
+    assert!(res.headers().get("transfer-encoding").is_none());
+    Ok(())
+}
+
+#[test]
+fn head_request() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("head")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_header(&res, "content-type", "application/octet-stream");
+    assert_header(&res, "content-length", "3");
+    assert_eq!(*res.body(), Body::Empty);
+    Ok(())
+}
+
+#[test]
+fn forced_host() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .force_host("api.example.com")
+        .into_handler();
+
+    let req = get_request("header_host")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("api.example.com".to_string()));
+    Ok(())
+}
+
+#[test]
+fn original_host() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("header_host")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("example.com".to_string()));
+    Ok(())
+}
+
+#[test]
+fn route_panic() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .echo_request_id(rocket_lamb::REQUEST_ID_HEADER)
+        .into_handler();
+
+    let req = get_request("panic")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 500);
+    assert_eq!(*res.body(), Body::Empty);
+    assert_header(&res, "x-amzn-requestid", "test-invoke-request");
+
+    let req = get_request("upper")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(*res.body(), Body::Text("ONE, TWO, THREE".to_string()));
+    Ok(())
+}
+
+#[test]
+fn echo_request_id() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .echo_request_id(rocket_lamb::REQUEST_ID_HEADER)
+        .into_handler();
+
+    let req = get_request("upper")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_header(&res, "x-amzn-requestid", "test-invoke-request");
+    Ok(())
+}
+
+#[test]
+fn echo_request_id_method_not_allowed() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .echo_request_id(rocket_lamb::REQUEST_ID_HEADER)
+        .into_handler();
+
+    let req = get_request("unknown_method")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 405);
+    assert_header(&res, "x-amzn-requestid", "test-invoke-request");
+    Ok(())
+}
+
+#[test]
+fn echo_request_id_request_too_large() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket()
+        .lambda()
+        .max_request_size(4)
+        .echo_request_id(rocket_lamb::REQUEST_ID_HEADER)
+        .into_handler();
+
+    let req = get_request("upper")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 413);
+    assert_header(&res, "x-amzn-requestid", "test-invoke-request");
+    Ok(())
+}
+
+#[test]
+#[should_panic]
+fn echo_request_id_invalid_header_name() {
+    make_rocket().lambda().echo_request_id("X-Request Id");
+}
+
+#[test]
+fn request_not_found() -> Result<(), Box<dyn Error>> {
+    let mut handler = make_rocket().lambda().into_handler();
+
+    let req = get_request("not_found")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 404);
+    assert_eq!(res.headers().contains_key("content-type"), false);
+    assert!(res.body().is_empty(), "Response body should be empty");
+    Ok(())
+}
+
+#[test]
+fn request_not_found_catcher_text() -> Result<(), Box<dyn Error>> {
+    let mut handler = rocket::ignite()
+        .register(catchers![not_found_json])
+        .lambda()
+        .into_handler();
+
+    let req = get_request("not_found")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 404);
+    assert_header(&res, "content-type", "application/json");
+    assert_eq!(
+        *res.body(),
+        Body::Text("{\"error\":\"not found\"}".to_string())
+    );
+    Ok(())
+}
+
+#[test]
+fn request_not_found_catcher_binary() -> Result<(), Box<dyn Error>> {
+    let mut handler = rocket::ignite()
+        .register(catchers![not_found_json])
+        .lambda()
+        .response_type("application/json", ResponseType::Binary)
+        .into_handler();
+
+    let req = get_request("not_found")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 404);
+    assert_header(&res, "content-type", "application/json");
+    assert_eq!(
+        *res.body(),
+        Body::Binary(b"{\"error\":\"not found\"}".to_vec())
+    );
+    Ok(())
+}
+
+fn assert_header(res: &Response<Body>, name: &str, value: &str) {
+    let values = res.headers().get_all(name).iter().collect::<Vec<_>>();
+    assert_eq!(values.len(), 1, "Header {} should have 1 value", name);
+    assert_eq!(values[0], value);
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
 }
@@ -0,0 +1,59 @@
+use lambda_http::{Body, Handler, Request};
+use lambda_runtime::Context;
+use rocket_contrib::serve::StaticFiles;
+use rocket_lamb::{EmbeddedFiles, RocketExt};
+use std::error::Error;
+use std::fs::File;
+
+const PIXEL: &[u8] = include_bytes!("static/pixel.gif");
+
+fn get_request(json_file: &str) -> Result<Request, Box<dyn Error>> {
+    let file = File::open(format!("tests/requests/{}.json", json_file))?;
+    Ok(lambda_http::request::from_reader(file)?)
+}
+
+#[test]
+fn static_files() -> Result<(), Box<dyn Error>> {
+    let mut handler = rocket::ignite()
+        .mount("/static", StaticFiles::from("tests/static"))
+        .lambda()
+        .into_handler();
+
+    let req = get_request("static_file")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-type"], "image/gif");
+    assert_eq!(*res.body(), Body::Binary(PIXEL.to_vec()));
+    Ok(())
+}
+
+#[test]
+fn embedded_files() -> Result<(), Box<dyn Error>> {
+    let mut handler = rocket::ignite()
+        .mount("/static", EmbeddedFiles::new(&[("pixel.gif", PIXEL)]))
+        .lambda()
+        .into_handler();
+
+    let req = get_request("static_file")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-type"], "image/gif");
+    assert_eq!(*res.body(), Body::Binary(PIXEL.to_vec()));
+    Ok(())
+}
+
+#[test]
+fn embedded_files_not_found() -> Result<(), Box<dyn Error>> {
+    let mut handler = rocket::ignite()
+        .mount("/static", EmbeddedFiles::new(&[("other.gif", PIXEL)]))
+        .lambda()
+        .into_handler();
+
+    let req = get_request("static_file")?;
+    let res = handler.run(req, Context::default())?;
+
+    assert_eq!(res.status(), 404);
+    Ok(())
+}